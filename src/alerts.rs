@@ -0,0 +1,244 @@
+use std::{collections::HashSet, error::Error};
+
+use crate::{
+    args::{self, AlertKind, AlertsArg},
+    codegen::camel_case,
+    files,
+    meta::{Level, Meta},
+    pattern::Pattern,
+};
+
+/// Alerting rule for one documented message, rendered as a Prometheus
+/// rule file entry (the Loki ruler uses the same format).
+#[derive(Debug)]
+pub struct Rule {
+    pub name: String,
+    pub expr: String,
+    pub severity: &'static str,
+    pub meta: Meta,
+}
+
+pub fn run(metas: &[Meta], arg: &args::Arg, alerts: &AlertsArg) -> Result<(), Box<dyn Error>> {
+    let rules = rules(metas, alerts);
+    let yaml = render(arg.project_name(), &rules);
+    let path = match &alerts.output {
+        Some(output) => output.clone(),
+        None => std::path::Path::new(&arg.save_path)
+            .join("alerts.yml")
+            .display()
+            .to_string(),
+    };
+    files::save(yaml, &path, arg.dry_run)?;
+    Ok(())
+}
+
+pub fn rules(metas: &[Meta], alerts: &AlertsArg) -> Vec<Rule> {
+    let levels: Vec<Level> = alerts.levels.iter().map(Level::from).collect();
+    let mut metas: Vec<Meta> = metas
+        .iter()
+        .filter(|m| {
+            if alerts.annotated {
+                m.tag("alert").is_some()
+            } else {
+                levels.contains(&m.level) || m.tag("alert").is_some()
+            }
+        })
+        .cloned()
+        .collect();
+    Meta::sort(&mut metas);
+    metas.reverse();
+
+    let mut names = HashSet::new();
+    let mut rules = vec![];
+    for meta in metas {
+        let pattern = Pattern::from(&meta.message.format());
+        let regex = pattern.to_regex();
+        let expr = match alerts.kind {
+            AlertKind::Loki => format!(
+                "sum(count_over_time({} |~ `{}` [{}])) > 0",
+                alerts.selector,
+                regex.replace('`', "\\x60"),
+                alerts.window
+            ),
+            AlertKind::Prometheus => format!(
+                "sum(increase({}{{{}=~{}}}[{}])) > 0",
+                alerts.metric,
+                alerts.label,
+                serde_json::Value::String(regex),
+                alerts.window
+            ),
+        };
+        let mut words = pattern.words();
+        words.truncate(6);
+        let base = format!("Log{}", camel_case(&words));
+        let mut name = base.clone();
+        let mut n = 2;
+        while !names.insert(name.clone()) {
+            name = format!("{}{}", base, n);
+            n += 1;
+        }
+        rules.push(Rule {
+            name,
+            expr,
+            severity: severity(&meta.level),
+            meta,
+        });
+    }
+    rules
+}
+
+fn severity(level: &Level) -> &'static str {
+    match level {
+        Level::Fatal => "critical",
+        Level::Warn => "warning",
+        Level::Info | Level::Debug | Level::Trace => "info",
+    }
+}
+
+pub fn render<T: AsRef<str>>(project: T, rules: &[Rule]) -> String {
+    let mut out = String::new();
+    out.push_str("groups:\n");
+    out.push_str(&format!(
+        "  - name: {}\n",
+        quote(&format!("{}-logs", project.as_ref()))
+    ));
+    if rules.is_empty() {
+        out.push_str("    rules: []\n");
+        return out;
+    }
+    out.push_str("    rules:\n");
+    for rule in rules {
+        let meta = &rule.meta;
+        out.push_str(&format!("      - alert: {}\n", rule.name));
+        out.push_str(&format!("        expr: {}\n", quote(&rule.expr)));
+        out.push_str("        labels:\n");
+        out.push_str(&format!("          severity: {}\n", rule.severity));
+        out.push_str(&format!("          level: {}\n", meta.level));
+        out.push_str(&format!("          message_id: {}\n", quote(&meta.id())));
+        out.push_str("        annotations:\n");
+        let summary = meta
+            .tag("alert")
+            .cloned()
+            .unwrap_or_else(|| meta.subject.format());
+        out.push_str(&format!("          summary: {}\n", quote(&summary)));
+        let description = meta.untagged_description();
+        let description = description.trim_end();
+        if !description.is_empty() {
+            out.push_str(&format!("          description: {}\n", quote(description)));
+        }
+        if let Some(action) = meta.tag("action") {
+            out.push_str(&format!("          action: {}\n", quote(action)));
+        }
+        if let Some(runbook) = meta.tag("runbook") {
+            out.push_str(&format!("          runbook_url: {}\n", quote(runbook)));
+        }
+        out.push_str(&format!(
+            "          message: {}\n",
+            quote(&meta.message.format())
+        ));
+        out.push_str(&format!(
+            "          source: {}\n",
+            quote(&format!("{}:{}", meta.file, meta.line))
+        ));
+    }
+    out
+}
+
+/// Double quoted YAML scalar; JSON string escaping is valid YAML.
+fn quote(s: &str) -> String {
+    serde_json::Value::String(s.to_owned()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::meta::{Description, Message, Subject};
+
+    use super::*;
+
+    fn metas() -> Vec<Meta> {
+        vec![
+            Meta {
+                level: Level::Fatal,
+                message: Message("database %s is unreachable".to_owned()),
+                subject: Subject("service can't store data\n".to_owned()),
+                description: Description(
+                    "requests fail\naction: restart the db\nuntil db is back\n".to_owned(),
+                ),
+                file: "db.go".to_owned(),
+                line: 10,
+                tags: BTreeMap::from([
+                    ("action".to_owned(), "restart the db".to_owned()),
+                    ("runbook".to_owned(), "https://wiki/runbooks/db".to_owned()),
+                ]),
+                ..Default::default()
+            },
+            Meta {
+                level: Level::Warn,
+                message: Message("cache miss rate is {}% (limit 5%)".to_owned()),
+                subject: Subject("cache is cold".to_owned()),
+                file: "cache.go".to_owned(),
+                line: 22,
+                ..Default::default()
+            },
+            Meta {
+                level: Level::Info,
+                message: Message("config reloaded".to_owned()),
+                subject: Subject("new config applied".to_owned()),
+                file: "config.go".to_owned(),
+                line: 5,
+                tags: BTreeMap::from([("alert".to_owned(), "config changed".to_owned())]),
+                ..Default::default()
+            },
+            Meta {
+                level: Level::Info,
+                message: Message("request served".to_owned()),
+                file: "http.go".to_owned(),
+                line: 40,
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn alerts_arg(kind: AlertKind) -> AlertsArg {
+        AlertsArg {
+            kind,
+            levels: vec![args::Level::Warn, args::Level::Fatal],
+            annotated: false,
+            selector: "{app=\"billing\"}".to_owned(),
+            metric: "log_messages_total".to_owned(),
+            label: "message".to_owned(),
+            window: "5m".to_owned(),
+            output: None,
+        }
+    }
+
+    #[test]
+    fn loki_golden() {
+        let rules = rules(&metas(), &alerts_arg(AlertKind::Loki));
+        assert_eq!(
+            render("billing", &rules),
+            include_str!("../tests/alerts/loki.yml")
+        );
+    }
+
+    #[test]
+    fn prometheus_golden() {
+        let rules = rules(&metas(), &alerts_arg(AlertKind::Prometheus));
+        assert_eq!(
+            render("billing", &rules),
+            include_str!("../tests/alerts/prometheus.yml")
+        );
+    }
+
+    #[test]
+    fn annotated_only() {
+        let mut arg = alerts_arg(AlertKind::Loki);
+        arg.annotated = true;
+        let rules = rules(&metas(), &arg);
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].name, "LogConfigReloaded");
+        assert_eq!(rules[0].severity, "info");
+    }
+}
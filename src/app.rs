@@ -1,7 +1,12 @@
 use clap::Parser;
+use streaming_iterator::StreamingIterator;
 
 use crate::args;
+use crate::calls::LogCall;
 use crate::files;
+use crate::findings::Finding;
+use crate::i18n::Headers;
+use crate::language::CommentLine;
 use crate::meta::Description;
 use crate::meta::Level;
 use crate::meta::Message;
@@ -10,89 +15,379 @@ use crate::meta::Pos;
 use crate::meta::Subject;
 use crate::meta::Typo;
 use crate::template::render;
+use crate::template::render_fragment;
 use crate::template::TemplateData;
 
 pub struct Application;
 
+/// Annotations of the scanned files, the malformed ones and every log call
+/// found while parsing them.
+#[derive(Debug, Default)]
+pub struct Scan {
+    pub metas: Vec<Meta>,
+    pub problems: Vec<Finding>,
+    pub calls: Vec<(String, LogCall)>,
+}
+
 impl Application {
     pub fn run() -> Result<(), Box<dyn std::error::Error>> {
         let arg = args::Arg::parse();
+        if arg.workspace.is_some() || arg.discover.is_some() {
+            return crate::workspace::run(&arg);
+        }
+        let scan = Self::collect(&arg)?;
+        Self::execute(&arg, &scan)
+    }
+
+    /// Run the requested command, or generate the documents, for `scan`.
+    pub fn execute(arg: &args::Arg, scan: &Scan) -> Result<(), Box<dyn std::error::Error>> {
+        let metas = &scan.metas;
+        match &arg.command {
+            Some(args::Command::Explain(explain)) => crate::explain::run(metas, explain),
+            Some(args::Command::Codegen(codegen)) => crate::codegen::run(metas, arg, codegen),
+            Some(args::Command::Alerts(alerts)) => crate::alerts::run(metas, arg, alerts),
+            Some(args::Command::Coverage(coverage)) => crate::coverage::run(scan, arg, coverage),
+            Some(args::Command::Check(check)) => crate::findings::run(scan, arg, check),
+            Some(args::Command::Show(show)) => crate::show::run(metas, arg, show),
+            None => Self::generate(arg, metas),
+        }
+    }
+
+    pub fn collect(arg: &args::Arg) -> Result<Scan, Box<dyn std::error::Error>> {
+        let lang = crate::language::Language::from(&arg.language()?);
+        let files = files::form_list_files(arg)?;
+        let mut scan = Self::scan_files(&lang, &files)?;
+        if let Some(args::Command::Check(_)) = arg.command {
+            // malformed annotations and duplicates are findings of `check`,
+            // subject to its baseline
+            return Ok(scan);
+        }
+        for problem in &scan.problems {
+            eprintln!("warning: {problem}");
+        }
+        scan.metas = crate::duplicates::check(std::mem::take(&mut scan.metas), &arg.duplicates)?;
+        Ok(scan)
+    }
+
+    /// Annotations of `files`, malformed ones are reported and skipped.
+    pub fn collect_files(
+        lang: &crate::language::Language,
+        files: &[String],
+    ) -> Result<Vec<Meta>, Box<dyn std::error::Error>> {
+        let scan = Self::scan_files(lang, files)?;
+        for problem in &scan.problems {
+            eprintln!("warning: {problem}");
+        }
+        Ok(scan.metas)
+    }
+
+    /// Annotations and log calls of `files`; malformed annotations are
+    /// skipped and kept as problems.
+    pub fn scan_files(
+        lang: &crate::language::Language,
+        files: &[String],
+    ) -> Result<Scan, Box<dyn std::error::Error>> {
         let mut parse = tree_sitter::Parser::new();
-        let lang = crate::language::Language::from(&arg.language);
         parse.set_language(&lang.sitter_language()).or(Err(format!(
             "Failed to load {} tree-sitter language",
             &lang
         )))?;
-        let files = files::form_list_files(&arg)?;
 
-        let query = tree_sitter::Query::new(&lang.sitter_language(), &lang.query())?;
+        let query = tree_sitter::Query::new(&lang.sitter_language(), lang.query())?;
         let language_comment = lang.comment();
 
-        let mut template_data_info =
-            TemplateData::new(&arg.project_name, Level::Info, &arg.info_desc);
-
-        let mut template_data_debug =
-            TemplateData::new(&arg.project_name, Level::Debug, &arg.debug_desc);
-
-        let mut template_data_trace =
-            TemplateData::new(&arg.project_name, Level::Trace, &arg.trace_desc);
-
-        let mut template_data_warn =
-            TemplateData::new(&arg.project_name, Level::Warn, &arg.warn_desc);
-
-        let mut template_data_fatal =
-            TemplateData::new(&arg.project_name, Level::Fatal, &arg.fatal_desc);
-
+        let mut scan = Scan::default();
         for file in files {
             let file_bytes = std::fs::read_to_string(file)?;
             let tree = parse
-                .parse(&file_bytes.as_bytes(), None)
+                .parse(file_bytes.as_bytes(), None)
                 .ok_or("Failed to parse data")?;
+            let module = lang.module(tree.root_node(), file_bytes.as_bytes(), file);
+            for call in crate::calls::find_all(tree.root_node(), file_bytes.as_bytes(), lang) {
+                scan.calls.push((file.clone(), call));
+            }
             let mut query_cursor = tree_sitter::QueryCursor::new();
-            let query_matches =
+            let mut query_matches =
                 query_cursor.matches(&query, tree.root_node(), file_bytes.as_bytes());
-            for query_match in query_matches {
-                let mut m = Meta::default();
+            while let Some(query_match) = query_matches.next() {
+                let mut m = Meta {
+                    file: file.clone(),
+                    module: module.clone(),
+                    ..Default::default()
+                };
+                let mut first_node = None;
+                let mut last_node = None;
+                let mut has_subject = false;
+                let mut malformed = None;
                 for query_capture in query_match.captures {
+                    first_node = first_node.or(Some(query_capture.node));
+                    last_node = Some(query_capture.node);
                     let position = Pos::from(query_capture);
-                    let query_bytes = files::search_in_file_dyn(&file_bytes.as_bytes(), &position);
-                    let data = String::from_utf8_lossy(&query_bytes).to_string();
+                    let text = query_capture.node.utf8_text(file_bytes.as_bytes())?;
+                    let (offset, lines) = match query_capture.node.kind() {
+                        "string_content" => docstring_annotation(text),
+                        _ => (0, language_comment.normalize(text)),
+                    };
+                    let mut lines = lines.into_iter();
                     if position.typo == Typo::Level {
-                        let level = Level::from((&data, &language_comment));
-                        m.level = level;
-                        m.message = Message::try_from((&data, &language_comment))?;
+                        if let Some(line) = lines.next() {
+                            m.level = Level::from(&line);
+                            m.line = position.start.0 as usize + 1 + offset;
+                            match Message::try_from(&line) {
+                                Ok(message) if !message.0.trim().is_empty() => m.message = message,
+                                _ => malformed = Some(line.text.clone()),
+                            }
+                        }
                     }
-                    if position.typo == Typo::Subject {
-                        m.subject = Subject::from((&data, &language_comment));
-                    }
-                    if position.typo == Typo::Description {
-                        let desc = Description::from((&data, &language_comment));
-                        let v = vec![m.description.0.clone(), desc.0];
-                        let v = Description::from((&v.join("").to_string(), &language_comment));
-                        m.description = v;
+                    for line in lines {
+                        if position.typo != Typo::Description && !has_subject {
+                            m.subject = Subject::from(&line);
+                            has_subject = true;
+                            continue;
+                        }
+                        let desc = Description::from(&line);
+                        // doc comment lines holding only XML tags
+                        if line.doc && desc.0.is_empty() {
+                            continue;
+                        }
+                        m.description.0.push_str(&desc.0);
+                        m.description.0.push('\n');
                     }
                 }
-                let tmeta = crate::template::TemplateMeta::from(&m);
-                match m.level {
-                    Level::Info => template_data_info.add_meta(tmeta),
-                    Level::Debug => template_data_debug.add_meta(tmeta),
-                    Level::Warn => template_data_warn.add_meta(tmeta),
-                    Level::Trace => template_data_trace.add_meta(tmeta),
-                    Level::Fatal => template_data_fatal.add_meta(tmeta),
+                if let Some(text) = malformed {
+                    scan.problems.push(Finding {
+                        rule: "malformed-annotation",
+                        file: file.clone(),
+                        line: m.line,
+                        message: format!("no message in \"{text}\""),
+                    });
+                    continue;
+                }
+                let docstring = first_node.filter(|node| node.kind() == "string_content");
+                m.call = match docstring {
+                    Some(docstring) => {
+                        crate::calls::docstring_call(docstring, file_bytes.as_bytes(), lang)
+                    }
+                    None => first_node.zip(last_node).and_then(|(first, last)| {
+                        crate::calls::find_call(first, last, file_bytes.as_bytes(), lang)
+                    }),
+                };
+                // a docstring not followed by a logging call documents code
+                if docstring.is_some() && m.call.is_none() {
+                    continue;
                 }
+                m.extract_tags();
+                scan.metas.push(m);
             }
         }
-        let template_str_info = render(template_data_info, &arg.save_type)?;
-        let template_str_debug = render(template_data_debug, &arg.save_type)?;
-        let template_str_warn = render(template_data_warn, &arg.save_type)?;
-        let template_str_trace = render(template_data_trace, &arg.save_type)?;
-        let template_str_fatal = render(template_data_fatal, &arg.save_type)?;
-        files::save_string_to_file(template_str_info, &Level::Info, &arg)?;
-        files::save_string_to_file(template_str_debug, &Level::Debug, &arg)?;
-        files::save_string_to_file(template_str_trace, &Level::Trace, &arg)?;
-        files::save_string_to_file(template_str_warn, &Level::Warn, &arg)?;
-        files::save_string_to_file(template_str_fatal, &Level::Fatal, &arg)?;
+        Ok(scan)
+    }
+
+    fn generate(arg: &args::Arg, metas: &[Meta]) -> Result<(), Box<dyn std::error::Error>> {
+        let outputs = Self::outputs(arg, metas)?;
+        if arg.check {
+            return files::check_files(&outputs);
+        }
+        for (path, data) in outputs {
+            files::save(data, path, arg.dry_run)?;
+        }
 
         Ok(())
     }
+
+    /// Paths and contents of the documents to generate, with a catalog in
+    /// `<save_path>/<locale>` for each translated locale.
+    pub fn outputs(
+        arg: &args::Arg,
+        metas: &[Meta],
+    ) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+        let mut outputs = Self::documents(arg, metas, &Headers::default())?;
+        if arg.inject.is_some() {
+            return Ok(outputs);
+        }
+        for catalog in crate::i18n::catalogs(arg, metas)? {
+            let (localized, untranslated) = catalog.localize(metas);
+            if !untranslated.is_empty() {
+                eprintln!(
+                    "warning: {}: {} of {} entries untranslated",
+                    catalog.locale,
+                    untranslated.len(),
+                    metas.len()
+                );
+                for entry in &untranslated {
+                    eprintln!("  {}:{} {}", entry.file, entry.line, entry.message);
+                }
+            }
+            let mut locale_arg = arg.clone();
+            locale_arg.save_path = std::path::Path::new(&arg.save_path)
+                .join(&catalog.locale)
+                .display()
+                .to_string();
+            outputs.extend(Self::documents(&locale_arg, &localized, &catalog.headers)?);
+        }
+        Ok(outputs)
+    }
+
+    fn documents(
+        arg: &args::Arg,
+        metas: &[Meta],
+        headers: &Headers,
+    ) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+        let mut outputs = vec![];
+        if let Some(documents) = &arg.inject {
+            use args::SaveType::*;
+            if !matches!(arg.save_type, MD | HTML | MDX) {
+                return Err(format!(
+                    "--inject can't be combined with the {} save type",
+                    arg.save_type
+                )
+                .into());
+            }
+            for path in documents {
+                let document = std::fs::read_to_string(path)?;
+                let updated = crate::inject::inject(&document, |level| {
+                    render_fragment(
+                        Self::template_data(arg, metas, level, headers),
+                        &arg.save_type,
+                    )
+                })
+                .map_err(|e| format!("{path}: {e}"))?;
+                outputs.push((path.clone(), updated));
+            }
+        } else if arg.save_type == args::SaveType::CSV && arg.csv_combined {
+            let mut data = Self::template_data(arg, &[], &Level::Info, headers);
+            data.level = "all".to_owned();
+            if !data.columns.iter().any(|c| c.key == "level") {
+                let level = crate::template::TemplateColumn::new(args::Column::Level);
+                data.columns.insert(0, level);
+            }
+            for level in Level::all() {
+                let level_data = Self::template_data(arg, metas, &level, headers);
+                data.metas.extend(level_data.metas);
+                for group in level_data.groups {
+                    for meta in group.metas {
+                        data.add_group_meta(group.name.clone(), meta);
+                    }
+                }
+            }
+            outputs.push((
+                std::path::Path::new(&arg.save_path)
+                    .join("logs.csv")
+                    .display()
+                    .to_string(),
+                render(data, &arg.save_type)?,
+            ));
+        } else if let Some(site) = &arg.site {
+            let levels = Level::all()
+                .into_iter()
+                .map(|level| (level, Self::template_data(arg, metas, &level, headers)))
+                .collect();
+            outputs.extend(crate::site::documents(arg, site, levels)?);
+        } else if arg.save_type == args::SaveType::MAN
+            && !(arg.group_by != args::GroupBy::None
+                && arg.group_output == args::GroupOutput::Files)
+        {
+            let levels: Vec<_> = Level::all()
+                .iter()
+                .map(|level| Self::template_data(arg, metas, level, headers))
+                .collect();
+            let name = format!("{}.7", crate::template::man_name(arg.project_name()));
+            outputs.push((
+                std::path::Path::new(&arg.save_path)
+                    .join(name)
+                    .display()
+                    .to_string(),
+                crate::template::render_man(&levels),
+            ));
+        } else if arg.group_by != args::GroupBy::None
+            && arg.group_output == args::GroupOutput::Files
+        {
+            let mut groups: Vec<String> = metas
+                .iter()
+                .filter_map(|m| m.group(&arg.group_by))
+                .collect();
+            groups.sort();
+            groups.dedup();
+            let dirs = files::group_dir_names(&groups);
+            for (group, dir) in groups.into_iter().zip(dirs) {
+                let group_metas: Vec<Meta> = metas
+                    .iter()
+                    .filter(|m| m.group(&arg.group_by).as_ref() == Some(&group))
+                    .cloned()
+                    .collect();
+                let dir = std::path::Path::new(&arg.save_path).join(dir);
+                for level in Level::all() {
+                    let mut data = Self::template_data(arg, &group_metas, &level, headers);
+                    data.metas
+                        .extend(data.groups.drain(..).flat_map(|g| g.metas));
+                    data.group = Some(group.clone());
+                    outputs.push((
+                        files::form_file_name(&dir.display().to_string(), arg, &level),
+                        render(data, &arg.save_type)?,
+                    ));
+                }
+            }
+        } else {
+            for level in Level::all() {
+                let data = Self::template_data(arg, metas, &level, headers);
+                let template_str = render(data, &arg.save_type)?;
+                outputs.push((
+                    files::form_file_name(&arg.save_path, arg, &level),
+                    template_str,
+                ));
+            }
+        }
+        Ok(outputs)
+    }
+
+    fn template_data(
+        arg: &args::Arg,
+        metas: &[Meta],
+        level: &Level,
+        headers: &Headers,
+    ) -> TemplateData {
+        let mut template_data = TemplateData::new(arg.project_name(), level, arg.level_desc(level));
+        template_data.columns = crate::template::columns(arg, headers);
+        template_data.csv_delimiter = arg.csv_delimiter;
+        let mut metas: Vec<Meta> = metas
+            .iter()
+            .filter(|m| m.level == *level)
+            .cloned()
+            .collect();
+        if let Some(order) = &arg.sort_by {
+            Meta::sort_by(&mut metas, order);
+        }
+        for m in &metas {
+            let tm = crate::template::TemplateMeta::from(m);
+            match m.group(&arg.group_by) {
+                Some(group) => template_data.add_group_meta(group, tm),
+                None => template_data.add_meta(tm),
+            }
+        }
+        template_data
+    }
+}
+
+/// Annotation inside a docstring: from the level line up to the next empty
+/// line, with the number of docstring lines before it.
+fn docstring_annotation(text: &str) -> (usize, Vec<CommentLine>) {
+    let lines: Vec<CommentLine> = text
+        .lines()
+        .map(|line| CommentLine {
+            text: line.trim().to_owned(),
+            doc: false,
+        })
+        .collect();
+    let Some(start) = lines
+        .iter()
+        .position(|line| Message::try_from(line).is_ok())
+    else {
+        return (0, vec![]);
+    };
+    let annotation = lines
+        .into_iter()
+        .skip(start)
+        .take_while(|line| !line.text.is_empty())
+        .collect();
+    (start, annotation)
 }
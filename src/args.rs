@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 use clap::Parser;
 
 #[derive(Debug, Parser, Clone)]
@@ -5,8 +7,8 @@ use clap::Parser;
 #[command(version, about="Create .MD files with information about logs", long_about=None)]
 pub struct Arg {
     /// Project name (used in generated files)
-    #[arg(short, long)]
-    pub project_name: String,
+    #[arg(short, long, required_unless_present_any = ["workspace", "discover"])]
+    pub project_name: Option<String>,
 
     /// Directories in which files are searched for processing
     #[arg(short, long)]
@@ -21,9 +23,9 @@ pub struct Arg {
     pub files: Option<Vec<String>>,
 
     /// Specify language that should be proceeded
-    #[arg(short, long)]
+    #[arg(short, long, required_unless_present_any = ["workspace", "discover"])]
     #[clap(value_parser)]
-    pub language: Language,
+    pub language: Option<Language>,
 
     /// Specify directory when data should be saved
     #[arg(short, long)]
@@ -74,73 +76,568 @@ pub struct Arg {
     #[arg(long = "description_table_header")]
     #[clap(env = "DESCRIPTION_TABLE_HEADER")]
     pub description_table_header: Option<String>,
+
+    /// Table columns in order
+    #[arg(long, value_delimiter = ',')]
+    #[clap(value_parser, default_value = "message,subject,description")]
+    pub columns: Vec<Column>,
+
+    /// Column title, e.g. `id=ID`
+    #[arg(long, value_parser = parse_column_title)]
+    pub column_title: Vec<(Column, String)>,
+
+    /// Relative column width in percent, e.g. `description=50`
+    #[arg(long, value_parser = parse_column_width)]
+    pub column_width: Vec<(Column, u8)>,
+
+    /// CSV field delimiter: `;`, `,` or `tab`
+    #[arg(long, value_parser = parse_csv_delimiter)]
+    #[clap(default_value = ";")]
+    pub csv_delimiter: u8,
+
+    /// Write the entries of all levels into one `logs.csv` with a level column
+    #[arg(long)]
+    pub csv_combined: bool,
+
+    /// Write a documentation section for a static site generator: pages
+    /// per level plus its navigation
+    #[arg(long)]
+    #[clap(value_parser)]
+    pub site: Option<Site>,
+
+    /// Path of the save directory inside the docs or book source, used in
+    /// the links of the site navigation
+    #[arg(long, default_value = "")]
+    pub site_prefix: String,
+
+    /// Front-matter field of the site pages, e.g. `tags=logs,{level}`;
+    /// `{project}` and `{level}` are replaced
+    #[arg(long, value_parser = parse_front_matter)]
+    pub front_matter: Vec<(String, String)>,
+
+    /// Markdown files to inject tables into, between
+    /// `<!-- logdoc:start level=... -->` and `<!-- logdoc:end -->` markers
+    #[arg(long)]
+    pub inject: Option<Vec<String>>,
+
+    /// Fail if generated files are not up to date instead of writing them
+    #[arg(long)]
+    pub check: bool,
+
+    /// Print which files would change without writing them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Group entries by package/module or by directory
+    #[arg(long)]
+    #[clap(value_parser, default_value = "none")]
+    pub group_by: GroupBy,
+
+    /// Render groups as sub-headings or as separate files per group
+    #[arg(long)]
+    #[clap(value_parser, default_value = "headings")]
+    pub group_output: GroupOutput,
+
+    /// Order of entries (source order when omitted)
+    #[arg(long)]
+    #[clap(value_parser)]
+    pub sort_by: Option<SortBy>,
+
+    /// Translation files (JSON keyed by message ID or gettext `.po`),
+    /// one catalog per locale is generated in `<save_path>/<locale>`
+    #[arg(long)]
+    pub translations: Option<Vec<String>>,
+
+    /// Locales to generate catalogs for besides those of translation files
+    /// and `subject[locale]:` annotations
+    #[arg(long, value_delimiter = ',')]
+    pub locales: Vec<String>,
+
+    /// Report duplicated messages, merge identical ones or fail the run
+    #[arg(long)]
+    #[clap(value_parser, default_value = "warn")]
+    pub duplicates: Duplicates,
+
+    /// Workspace config (TOML) listing projects to process in one run
+    #[arg(long, conflicts_with = "discover")]
+    pub workspace: Option<String>,
+
+    /// Discover projects under a directory by marker files (go.mod, Cargo.toml, ...)
+    #[arg(long)]
+    pub discover: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, clap::Subcommand, Clone)]
+pub enum Command {
+    /// Annotate log lines with the documentation of the matching messages
+    Explain(ExplainArg),
+    /// Generate source code with constants for each documented message
+    Codegen(CodegenArg),
+    /// Generate Prometheus or Loki alerting rules for important messages
+    Alerts(AlertsArg),
+    /// Report how many log calls are documented
+    Coverage(CoverageArg),
+    /// Report undocumented log calls and duplicated messages not in the baseline
+    Check(CheckArg),
+    /// Print the entries in the terminal
+    Show(ShowArg),
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct ShowArg {
+    /// Levels to show (all when omitted)
+    #[arg(long, value_delimiter = ',')]
+    #[clap(value_parser)]
+    pub level: Vec<Level>,
+
+    /// Show entries whose message, subject or description contain the text, ignoring case
+    #[arg(long)]
+    pub grep: Option<String>,
+
+    /// Colors by level
+    #[arg(long)]
+    #[clap(value_parser, default_value = "auto")]
+    pub color: Color,
+
+    /// Line width (`COLUMNS` or 80 when omitted)
+    #[arg(long)]
+    pub width: Option<usize>,
+}
+
+#[derive(Debug, clap::ValueEnum, Default, Clone, PartialEq, Eq)]
+pub enum Color {
+    /// When writing to a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct ExplainArg {
+    /// File with log lines (stdin when omitted)
+    #[arg(short, long)]
+    pub input: Option<String>,
+
+    /// Format of the log lines
+    #[arg(long)]
+    #[clap(value_parser, default_value = "plain")]
+    pub format: InputFormat,
+
+    /// Field holding the message in logfmt and json lines
+    #[arg(long)]
+    #[clap(default_value = "msg")]
+    pub message_field: String,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct CodegenArg {
+    /// Language of the generated code (language of sources when omitted)
+    #[arg(long)]
+    #[clap(value_parser)]
+    pub target: Option<Language>,
+
+    /// Package, module or class name of the generated code
+    #[arg(long)]
+    pub package: Option<String>,
+
+    /// File to write generated code to (file in save path when omitted)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct AlertsArg {
+    /// Rule flavour to generate
+    #[arg(long)]
+    #[clap(value_parser, default_value = "loki")]
+    pub kind: AlertKind,
+
+    /// Levels of messages that raise alerts
+    #[arg(long, value_delimiter = ',')]
+    #[clap(value_parser, default_value = "warn,fatal")]
+    pub levels: Vec<Level>,
+
+    /// Only messages with an `alert:` annotation raise alerts
+    #[arg(long)]
+    pub annotated: bool,
+
+    /// Loki stream selector of the service logs
+    #[arg(long)]
+    #[clap(default_value = "{job=~\".+\"}")]
+    pub selector: String,
+
+    /// Prometheus metric counting log messages
+    #[arg(long)]
+    #[clap(default_value = "log_messages_total")]
+    pub metric: String,
+
+    /// Label of the Prometheus metric holding the message
+    #[arg(long)]
+    #[clap(default_value = "message")]
+    pub label: String,
+
+    /// Range over which messages are counted
+    #[arg(long)]
+    #[clap(default_value = "5m")]
+    pub window: String,
+
+    /// File to write rules to (alerts.yml in save path when omitted)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Parser, clap::ValueEnum, Default, Clone, PartialEq, Eq)]
+pub enum AlertKind {
+    #[default]
+    Loki,
+    Prometheus,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct CheckArg {
+    /// Findings accepted so far, only findings not in it are reported
+    #[arg(long)]
+    #[clap(default_value = "logdoc-baseline.json")]
+    pub baseline: String,
+
+    /// Record the current findings as the baseline
+    #[arg(long)]
+    pub write_baseline: bool,
+
+    /// Format of the reported findings
+    #[arg(long)]
+    #[clap(value_parser, default_value = "text")]
+    pub format: FindingsFormat,
+
+    /// File to write the findings to (stdout when omitted)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, clap::ValueEnum, Default, Clone, PartialEq, Eq)]
+pub enum FindingsFormat {
+    /// `file:line: rule: message`
+    #[default]
+    Text,
+    /// One JSON object per line
+    JsonLines,
+    /// SARIF 2.1.0 log
+    Sarif,
+    /// GitHub Actions `::warning` workflow commands
+    Github,
 }
 
-#[derive(Debug, Parser, clap::ValueEnum, Clone)]
+#[derive(Debug, clap::Args, Clone)]
+pub struct CoverageArg {
+    /// Report format
+    #[arg(long)]
+    #[clap(value_parser, default_value = "text")]
+    pub format: CoverageFormat,
+
+    /// Minimal coverage in percent of all calls (`80`) or of a level (`fatal=100`)
+    #[arg(long, value_parser = parse_min_coverage)]
+    pub min_coverage: Vec<MinCoverage>,
+
+    /// File to write the report to (stdout when omitted)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, clap::ValueEnum, Default, Clone, PartialEq, Eq)]
+pub enum CoverageFormat {
+    #[default]
+    Text,
+    Json,
+    /// Badge and tables for a README or pull request comment
+    Markdown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinCoverage {
+    /// Level the threshold applies to, all calls when `None`
+    pub level: Option<crate::meta::Level>,
+    pub percent: f64,
+}
+
+fn parse_min_coverage(value: &str) -> Result<MinCoverage, String> {
+    let (level, percent) = match value.split_once('=') {
+        Some((level, percent)) => {
+            let level = <Level as clap::ValueEnum>::from_str(level, true)?;
+            (Some(crate::meta::Level::from(&level)), percent)
+        }
+        None => (None, value),
+    };
+    let percent = percent
+        .trim_end_matches('%')
+        .parse::<f64>()
+        .map_err(|e| format!("{percent}: {e}"))?;
+    Ok(MinCoverage { level, percent })
+}
+
+#[derive(Debug, Parser, clap::ValueEnum, Clone, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Debug,
+    Trace,
+    Warn,
+    Fatal,
+}
+
+#[derive(Debug, Parser, clap::ValueEnum, Default, Clone, PartialEq, Eq)]
+pub enum InputFormat {
+    #[default]
+    Plain,
+    Logfmt,
+    Json,
+}
+
+#[derive(Debug, clap::ValueEnum, Clone, PartialEq, Eq)]
 pub enum Language {
     Golang,
     C,
     Cpp,
     Python,
     Java,
+    #[value(name = "javascript", alias = "java-script")]
     JavaScript,
+    #[value(name = "typescript")]
+    TypeScript,
+    Tsx,
+    #[value(name = "csharp")]
+    CSharp,
+    Kotlin,
+    Php,
+    Bash,
+    Lua,
     Ruby,
     Rust,
 }
 
+#[derive(Debug, clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Message,
+    Subject,
+    Description,
+    Level,
+    /// `file:line` of the annotation
+    Location,
+    File,
+    Line,
+    /// Stable message ID
+    Id,
+    /// Package, module or namespace
+    Module,
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Column::Message => "message",
+            Column::Subject => "subject",
+            Column::Description => "description",
+            Column::Level => "level",
+            Column::Location => "location",
+            Column::File => "file",
+            Column::Line => "line",
+            Column::Id => "id",
+            Column::Module => "module",
+        };
+        write!(f, "{name}")
+    }
+}
+
+fn parse_column_title(value: &str) -> Result<(Column, String), String> {
+    let (column, title) = value
+        .split_once('=')
+        .ok_or(format!("expected column=title, got {value}"))?;
+    let column = <Column as clap::ValueEnum>::from_str(column.trim(), true)?;
+    Ok((column, title.to_owned()))
+}
+
+fn parse_column_width(value: &str) -> Result<(Column, u8), String> {
+    let (column, width) = parse_column_title(value)?;
+    let width = width
+        .trim()
+        .trim_end_matches('%')
+        .parse::<u8>()
+        .map_err(|e| format!("{width}: {e}"))?;
+    if width == 0 || width > 100 {
+        return Err(format!("width {width} is not a percent"));
+    }
+    Ok((column, width))
+}
+
+fn parse_csv_delimiter(value: &str) -> Result<u8, String> {
+    match value {
+        "," | "comma" => Ok(b','),
+        ";" | "semicolon" => Ok(b';'),
+        "\t" | "tab" => Ok(b'\t'),
+        _ => Err(format!(
+            "unsupported delimiter {value}, expected `,`, `;` or `tab`"
+        )),
+    }
+}
+
+#[derive(Debug, clap::ValueEnum, Default, Clone, PartialEq, Eq)]
+pub enum Duplicates {
+    Ignore,
+    /// Print duplicates as warnings
+    #[default]
+    Warn,
+    /// Merge identical entries into one listing all locations
+    Merge,
+    /// Fail when any duplicate is found
+    Fail,
+}
+
 #[derive(Debug, Parser, clap::ValueEnum, Default, Clone, PartialEq, Eq)]
 pub enum SaveType {
     #[default]
     MD,
     CSV,
+    HTML,
+    JSON,
+    /// Markdown for MDX, e.g. for Docusaurus
+    MDX,
+    /// AsciiDoc, e.g. for Antora
+    ADOC,
+    /// reStructuredText, e.g. for Sphinx
+    RST,
+    /// roff man page of section 7 with all levels
+    MAN,
+}
+
+#[derive(Debug, clap::ValueEnum, Clone, PartialEq, Eq)]
+pub enum Site {
+    /// Chapters and a `SUMMARY.md` fragment
+    #[value(name = "mdbook")]
+    MdBook,
+    /// MDX pages with front-matter and a `sidebar.json` category
+    Docusaurus,
+    /// Pages with front-matter and a `mkdocs-nav.yml` nav snippet
+    #[value(name = "mkdocs")]
+    MkDocs,
+}
+
+fn parse_front_matter(value: &str) -> Result<(String, String), String> {
+    let (key, value) = value
+        .split_once('=')
+        .ok_or(format!("expected key=value, got {value}"))?;
+    let key = key.trim();
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!("invalid front-matter key `{key}`"));
+    }
+    Ok((key.to_owned(), value.to_owned()))
+}
+
+#[derive(Debug, Parser, clap::ValueEnum, Default, Clone, PartialEq, Eq)]
+pub enum GroupBy {
+    #[default]
+    None,
+    /// Go/Java package, Python module, Rust module path, C#/PHP namespace;
+    /// directory for other languages
+    Package,
+    Directory,
 }
 
-impl ToString for Language {
-    fn to_string(&self) -> String {
+#[derive(Debug, Parser, clap::ValueEnum, Default, Clone, PartialEq, Eq)]
+pub enum GroupOutput {
+    #[default]
+    Headings,
+    Files,
+}
+
+#[derive(Debug, Parser, clap::ValueEnum, Clone, PartialEq, Eq)]
+pub enum SortBy {
+    Message,
+    Location,
+    Severity,
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use Language::*;
         match self {
-            Golang => "golang".to_owned(),
-            C => "c".to_owned(),
-            Cpp => "cpp".to_owned(),
-            Python => "python".to_owned(),
-            Java => "java".to_owned(),
-            JavaScript => "javascript".to_owned(),
-            Ruby => "ruby".to_owned(),
-            Rust => "rust".to_owned(),
+            Golang => write!(f, "golang"),
+            C => write!(f, "c"),
+            Cpp => write!(f, "cpp"),
+            Python => write!(f, "python"),
+            Java => write!(f, "java"),
+            JavaScript => write!(f, "javascript"),
+            TypeScript => write!(f, "typescript"),
+            Tsx => write!(f, "tsx"),
+            CSharp => write!(f, "csharp"),
+            Kotlin => write!(f, "kotlin"),
+            Php => write!(f, "php"),
+            Bash => write!(f, "bash"),
+            Lua => write!(f, "lua"),
+            Ruby => write!(f, "ruby"),
+            Rust => write!(f, "rust"),
         }
     }
 }
 
-impl ToString for SaveType {
-    fn to_string(&self) -> String {
+impl Display for SaveType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use SaveType::*;
         match self {
-            MD => "markdown".to_owned(),
-            CSV => "csv".to_owned(),
+            MD => write!(f, "markdown"),
+            CSV => write!(f, "csv"),
+            HTML => write!(f, "html"),
+            JSON => write!(f, "json"),
+            MDX => write!(f, "mdx"),
+            ADOC => write!(f, "asciidoc"),
+            RST => write!(f, "restructuredtext"),
+            MAN => write!(f, "man"),
         }
     }
 }
 
 impl Arg {
+    pub fn project_name(&self) -> &str {
+        self.project_name.as_deref().unwrap_or_default()
+    }
+    pub fn language(&self) -> Result<Language, Box<dyn std::error::Error>> {
+        self.language.clone().ok_or("language is not set".into())
+    }
     pub fn directories(&self) -> Vec<String> {
-        let dirs = if let Some(dirs) = self.directories.clone() {
-            dirs
-        } else {
-            vec![]
-        };
-        dirs
+        self.directories.clone().unwrap_or_default()
     }
     pub fn directories_ref(&self) -> Vec<&String> {
         if let Some(dirs) = &self.directories {
-            let v = dirs.iter().map(|x| x).collect();
+            let v = dirs.iter().collect();
             return v;
         }
-        return vec![];
+        vec![]
+    }
+    pub fn level_desc(&self, level: &crate::meta::Level) -> &Option<String> {
+        use crate::meta::Level;
+        match level {
+            Level::Info => &self.info_desc,
+            Level::Debug => &self.debug_desc,
+            Level::Trace => &self.trace_desc,
+            Level::Warn => &self.warn_desc,
+            Level::Fatal => &self.fatal_desc,
+        }
     }
     pub fn file_suffix(&self) -> String {
         match self.save_type {
             SaveType::MD => "md".to_owned(),
             SaveType::CSV => "csv".to_owned(),
+            SaveType::HTML => "html".to_owned(),
+            SaveType::JSON => "json".to_owned(),
+            SaveType::MDX => "mdx".to_owned(),
+            SaveType::ADOC => "adoc".to_owned(),
+            SaveType::RST => "rst".to_owned(),
+            SaveType::MAN => "7".to_owned(),
         }
     }
 }
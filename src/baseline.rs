@@ -0,0 +1,143 @@
+use std::{error::Error, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{duplicates::normalize, findings::Finding};
+
+/// Findings accepted when strict checking was turned on. Line numbers are
+/// left out so edits elsewhere in a file keep its entries matching, files
+/// are relative to the directory of the baseline.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Baseline {
+    pub findings: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Entry {
+    pub file: String,
+    pub rule: String,
+    /// Message normalized like near-duplicates (see `duplicates::normalize`)
+    pub message: String,
+}
+
+impl Entry {
+    /// Entry of `finding` with its file relative to `root`.
+    pub fn new(finding: &Finding, root: &Path) -> Entry {
+        Entry {
+            file: relative_file(root, &finding.file),
+            rule: finding.rule.to_owned(),
+            message: normalize(&finding.message),
+        }
+    }
+}
+
+/// `file` relative to `root` with `/` separators: `./src/x.go` and
+/// `src/x.go` are the same file.
+fn relative_file(root: &Path, file: &str) -> String {
+    let root = if root.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        root
+    };
+    let path = Path::new(file);
+    let relative = match (std::path::absolute(root), std::path::absolute(path)) {
+        (Ok(root), Ok(absolute)) => absolute
+            .strip_prefix(&root)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    };
+    relative
+        .components()
+        .filter(|c| *c != std::path::Component::CurDir)
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl Baseline {
+    /// Baseline of `findings`, files relative to `root`.
+    pub fn new(findings: &[Finding], root: &Path) -> Baseline {
+        let mut findings: Vec<Entry> = findings.iter().map(|f| Entry::new(f, root)).collect();
+        findings.sort();
+        Baseline { findings }
+    }
+
+    /// Baseline at `path`, empty when the file doesn't exist.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Baseline, Box<dyn Error>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Baseline::default());
+        }
+        let data = std::fs::read_to_string(path)?;
+        let baseline =
+            serde_json::from_str(&data).map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(baseline)
+    }
+
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// Findings not in the baseline and baseline entries no longer found,
+    /// files relative to `root`. Each entry matches one finding, so a second
+    /// copy of a known problem is new.
+    pub fn compare<'a>(
+        &self,
+        findings: &'a [Finding],
+        root: &Path,
+    ) -> (Vec<&'a Finding>, Vec<Entry>) {
+        let mut remaining = self.findings.clone();
+        let mut new = vec![];
+        for finding in findings {
+            let entry = Entry::new(finding, root);
+            match remaining.iter().position(|e| *e == entry) {
+                Some(i) => {
+                    remaining.remove(i);
+                }
+                None => new.push(finding),
+            }
+        }
+        (new, remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::findings::Finding;
+
+    use super::Baseline;
+
+    fn finding(line: usize, message: &str) -> Finding {
+        Finding {
+            rule: "undocumented",
+            file: "./src/main.go".to_owned(),
+            line,
+            message: message.to_owned(),
+        }
+    }
+
+    #[test]
+    fn compare() {
+        let old = [
+            finding(10, "info call log.Info \"started %s\""),
+            finding(20, "warn call log.Warn \"retry\""),
+        ];
+        let baseline = Baseline::new(&old, Path::new(""));
+        assert_eq!(baseline.findings[0].file, "src/main.go");
+        let json = baseline.to_json().unwrap();
+        assert_eq!(serde_json::from_str::<Baseline>(&json).unwrap(), baseline);
+
+        let mut current = [
+            finding(14, "info call log.Info \"started {}\""),
+            finding(30, "fatal call log.Fatal \"crashed\""),
+        ];
+        current[0].file = "src/main.go".to_owned();
+        let (new, resolved) = baseline.compare(&current, Path::new("."));
+        assert_eq!(new, [&current[1]]);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].message, "warn call log.warn \"retry\"");
+    }
+}
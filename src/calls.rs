@@ -0,0 +1,704 @@
+use tree_sitter::Node;
+
+use crate::{language::Language, meta::Level};
+
+/// Logging call following an annotation block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogCall {
+    /// Callee as written in the source, e.g. `_logger.LogWarning`.
+    pub function: String,
+    /// Content of the first string literal argument.
+    pub message: Option<String>,
+    pub line: usize,
+    /// Log target, e.g. `target: "db"` of Rust `log`/`tracing` macros.
+    pub target: Option<String>,
+    /// Keys of structured fields passed with the message.
+    pub fields: Vec<String>,
+    /// Span the call is logged in: a Rust function marked `#[instrument]`.
+    pub span: Option<String>,
+}
+
+/// Method names (lowercase, last path segment of the callee) treated as
+/// logging calls.
+pub const LOG_METHODS: &[&str] = &[
+    "info",
+    "infof",
+    "infoln",
+    "debug",
+    "debugf",
+    "debugln",
+    "trace",
+    "tracef",
+    "warn",
+    "warnf",
+    "warning",
+    "warningf",
+    "error",
+    "errorf",
+    "errorln",
+    "fatal",
+    "fatalf",
+    "fatalln",
+    "critical",
+    "exception",
+    "panic",
+    "panicf",
+    "print",
+    "printf",
+    "println",
+    "log",
+    "loginformation",
+    "logdebug",
+    "logtrace",
+    "logwarning",
+    "logerror",
+    "logcritical",
+    "error_log",
+    "msg",
+    "success",
+    "event",
+    "debugw",
+    "infow",
+    "warnw",
+    "errorw",
+    "fatalw",
+];
+
+/// Receivers whose `Errorf`/`Fatalf` style methods don't log: Go `fmt` and
+/// `errors` build error values, `t`, `b` and `tb` are `testing` helpers.
+const NON_LOGGERS: &[&str] = &["fmt", "errors", "t", "b", "tb"];
+
+impl LogCall {
+    /// Last path segment of the callee, lowercase: `logwarning` for
+    /// `_logger.LogWarning`.
+    pub fn method(&self) -> String {
+        method_name(&self.function)
+    }
+
+    /// Level logged by the call judging by its method, none for receivers
+    /// that don't log (see `NON_LOGGERS`): `warning` is warn, `error`,
+    /// `exception` and `critical` are fatal. Printf (`Infof`),
+    /// println (`Infoln`) and zap sugared (`Infow`) variants log at the level
+    /// of their base method.
+    pub fn level(&self) -> Option<Level> {
+        let receiver = self.function.rsplit(['.', ':', '>', ' ']).nth(1);
+        if receiver.is_some_and(|receiver| NON_LOGGERS.contains(&receiver)) {
+            return None;
+        }
+        let method = self.method();
+        let method = method.strip_prefix("log").unwrap_or(&method);
+        let method = method
+            .strip_suffix("ln")
+            .or_else(|| method.strip_suffix('f'))
+            .or_else(|| method.strip_suffix('w'))
+            .unwrap_or(method);
+        match method {
+            "info" | "information" | "success" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            "warn" | "warning" => Some(Level::Warn),
+            "error" | "fatal" | "critical" | "exception" | "panic" => Some(Level::Fatal),
+            _ => None,
+        }
+    }
+}
+
+fn method_name(function: &str) -> String {
+    function
+        .rsplit(['.', ':', '>', ' '])
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('!')
+        .to_lowercase()
+}
+
+/// Logging call documented by the annotation block `first`..`last`: the
+/// statement the block trails on the same line (`log.info("x")  # info: x`),
+/// otherwise the statement that follows the block.
+pub fn find_call(first: Node, last: Node, source: &[u8], lang: &Language) -> Option<LogCall> {
+    let first = statement_of(first);
+    if let Some(previous) = first.prev_named_sibling() {
+        if previous.end_position().row == first.start_position().row
+            && !previous.kind().contains("comment")
+        {
+            if let Some(call) = find_in(previous, source, lang) {
+                return Some(call);
+            }
+        }
+    }
+    let mut statement = statement_of(last).next_named_sibling();
+    while let Some(node) = statement {
+        if !node.kind().contains("comment") {
+            break;
+        }
+        statement = node.next_named_sibling();
+    }
+    find_in(statement?, source, lang)
+}
+
+/// Logging call of the statement right after the docstring holding
+/// `content`; a docstring is an annotation only when one follows it.
+pub fn docstring_call(content: Node, source: &[u8], lang: &Language) -> Option<LogCall> {
+    let mut statement = statement_of(content).next_named_sibling();
+    while let Some(node) = statement {
+        if !node.kind().contains("comment") {
+            break;
+        }
+        statement = node.next_named_sibling();
+    }
+    find_in(
+        statement.filter(|node| node.kind() == "expression_statement")?,
+        source,
+        lang,
+    )
+}
+
+/// Every logging call under `node`, documented or not.
+pub fn find_all(node: Node, source: &[u8], lang: &Language) -> Vec<LogCall> {
+    if lang.call_kinds().contains(&node.kind()) {
+        if let Some(call) = as_log_call(node, source, lang) {
+            return vec![call];
+        }
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children
+        .into_iter()
+        .flat_map(|child| find_all(child, source, lang))
+        .collect()
+}
+
+/// Docstrings are captured by their content, links start at the statement.
+fn statement_of(node: Node) -> Node {
+    let mut node = node;
+    while matches!(node.kind(), "string_content" | "string") {
+        match node.parent() {
+            Some(parent) => node = parent,
+            None => break,
+        }
+    }
+    node
+}
+
+fn find_in(node: Node, source: &[u8], lang: &Language) -> Option<LogCall> {
+    if lang.call_kinds().contains(&node.kind()) {
+        if let Some(call) = as_log_call(node, source, lang) {
+            return Some(call);
+        }
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children
+        .into_iter()
+        .find_map(|child| find_in(child, source, lang))
+}
+
+fn as_log_call(node: Node, source: &[u8], lang: &Language) -> Option<LogCall> {
+    match lang {
+        Language::Bash => return as_shell_call(node, source),
+        Language::Golang => return go_call(node, source, lang),
+        _ => {}
+    }
+    let callee = match lang {
+        // Kotlin calls have no fields: the callee comes first, then the
+        // arguments and/or a trailing lambda (`logger.warn { "..." }`)
+        Language::Kotlin => node.named_child(0),
+        _ => ["function", "macro", "method", "name"]
+            .iter()
+            .find_map(|field| node.child_by_field_name(field)),
+    };
+    let callee_end = callee?.end_byte();
+    let function = std::str::from_utf8(&source[node.start_byte()..callee_end])
+        .ok()?
+        .trim()
+        .to_owned();
+    if !LOG_METHODS.contains(&method_name(&function).as_str()) {
+        return None;
+    }
+    if node.kind() == "macro_invocation" {
+        return Some(macro_call(node, function, source));
+    }
+    let message = first_string(node, callee_end, source, lang);
+    let fields = match lang {
+        Language::Python => python_fields(node, source),
+        _ => vec![],
+    };
+    Some(LogCall {
+        function,
+        message,
+        line: node.start_position().row + 1,
+        fields,
+        ..Default::default()
+    })
+}
+
+/// Keyword arguments of a Python logging call: keys of `extra={...}` and
+/// structlog style `key=value` pairs.
+fn python_fields(node: Node, source: &[u8]) -> Vec<String> {
+    const OPTIONS: &[&str] = &["exc_info", "stack_info", "stacklevel"];
+    let text = |n: Node| n.utf8_text(source).unwrap_or_default().to_owned();
+    let mut fields = vec![];
+    let Some(arguments) = node.child_by_field_name("arguments") else {
+        return fields;
+    };
+    let mut cursor = arguments.walk();
+    for argument in arguments.named_children(&mut cursor) {
+        if argument.kind() != "keyword_argument" {
+            continue;
+        }
+        let (Some(name), Some(value)) = (
+            argument.child_by_field_name("name"),
+            argument.child_by_field_name("value"),
+        ) else {
+            continue;
+        };
+        let name = text(name);
+        if name == "extra" && value.kind() == "dictionary" {
+            let mut cursor = value.walk();
+            for pair in value.named_children(&mut cursor) {
+                let key = pair
+                    .child_by_field_name("key")
+                    .filter(|key| key.kind() == "string");
+                fields.extend(key.map(|key| unquote(&text(key))));
+            }
+        } else if !OPTIONS.contains(&name.as_str()) {
+            fields.push(name);
+        }
+    }
+    fields
+}
+
+/// Arguments of Rust `log`, `tracing` and `slog` macros:
+/// `warn!(target: "db", user_id, "connection lost: {}", err)`,
+/// `error!(user.id = %id, ?err, "failed")`,
+/// `info!(log, "started"; "port" => port)`.
+fn macro_call(node: Node, function: String, source: &[u8]) -> LogCall {
+    let text = |n: &Node| n.utf8_text(source).unwrap_or_default();
+    let mut call = LogCall {
+        function,
+        line: node.start_position().row + 1,
+        span: instrument_span(node, source),
+        ..Default::default()
+    };
+    let mut cursor = node.walk();
+    let Some(tree) = node
+        .named_children(&mut cursor)
+        .find(|child| child.kind() == "token_tree")
+    else {
+        return call;
+    };
+    let mut cursor = tree.walk();
+    let tokens: Vec<Node> = tree.children(&mut cursor).collect();
+    let tokens = tokens
+        .get(1..tokens.len().saturating_sub(1))
+        .unwrap_or_default();
+
+    // (tokens of the argument, after the `;` of slog key-values)
+    let mut args: Vec<(Vec<Node>, bool)> = vec![];
+    let mut current = vec![];
+    let mut key_values = false;
+    for token in tokens {
+        match token.kind() {
+            "," | ";" => {
+                args.push((std::mem::take(&mut current), key_values));
+                key_values = key_values || token.kind() == ";";
+            }
+            _ => current.push(*token),
+        }
+    }
+    if !current.is_empty() {
+        args.push((current, key_values));
+    }
+    let slog = call.function.starts_with("slog") || key_values;
+    // slog macros take the logger first
+    let skip = usize::from(slog);
+
+    for (arg, key_value) in args.into_iter().skip(skip) {
+        let texts: Vec<&str> = arg.iter().map(text).collect();
+        if key_value {
+            if let Some(key) = texts.first() {
+                call.fields.push(unquote(key));
+            }
+            continue;
+        }
+        if call.message.is_some() {
+            // format arguments
+            continue;
+        }
+        match texts.as_slice() {
+            ["target", ":", target] => call.target = Some(unquote(target)),
+            ["parent" | "name", ":", ..] => {}
+            [literal] if arg[0].kind().ends_with("string_literal") => {
+                call.message = Some(unquote(literal))
+            }
+            _ if texts.contains(&"::") => {
+                // `Level::WARN` of `event!`
+            }
+            _ => {
+                let key = match texts.iter().position(|t| *t == "=") {
+                    Some(eq) => &texts[..eq],
+                    None => &texts[..],
+                };
+                let key = key.concat();
+                let key = key.trim_start_matches(['%', '?']);
+                if !key.is_empty() {
+                    call.fields.push(unquote(key));
+                }
+            }
+        }
+    }
+    call
+}
+
+/// Name of the span of a Rust function marked `#[instrument]` around
+/// `node`: the `name = "..."` argument or the function name.
+fn instrument_span(node: Node, source: &[u8]) -> Option<String> {
+    let text = |n: Node| n.utf8_text(source).unwrap_or_default();
+    let mut function = node;
+    while function.kind() != "function_item" {
+        function = function.parent()?;
+    }
+    let mut sibling = function.prev_named_sibling();
+    while let Some(attribute) = sibling {
+        match attribute.kind() {
+            "attribute_item" if text(attribute).contains("instrument") => {
+                let name = text(attribute)
+                    .split_once("name")
+                    .and_then(|(_, rest)| rest.trim_start().strip_prefix('='))
+                    .and_then(|rest| rest.trim_start().strip_prefix('"'))
+                    .and_then(|rest| rest.split_once('"'))
+                    .map(|(name, _)| name.to_owned());
+                return name.or_else(|| {
+                    function
+                        .child_by_field_name("name")
+                        .map(|name| text(name).to_owned())
+                });
+            }
+            "attribute_item" | "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        sibling = attribute.prev_named_sibling();
+    }
+    None
+}
+
+/// Go loggers: `slog.Warn("msg", "key", v, slog.Int("n", n))`, zap
+/// `logger.Error("msg", zap.String("url", u))`, logrus
+/// `logrus.WithField("user", u).Errorf(...)` and zerolog chains
+/// `log.Warn().Str("user", u).Msg("msg")`.
+fn go_call(node: Node, source: &[u8], lang: &Language) -> Option<LogCall> {
+    let text = |n: Node| n.utf8_text(source).unwrap_or_default().to_owned();
+    // (method, arguments) of the call chain, outermost call first
+    let mut segments = vec![];
+    let mut root = None;
+    let mut current = node;
+    while current.kind() == "call_expression" {
+        let function = current.child_by_field_name("function")?;
+        let arguments = current.child_by_field_name("arguments")?;
+        if function.kind() != "selector_expression" {
+            segments.push((text(function), arguments));
+            break;
+        }
+        segments.push((text(function.child_by_field_name("field")?), arguments));
+        current = function.child_by_field_name("operand")?;
+        if current.kind() != "call_expression" {
+            root = Some(text(current));
+        }
+    }
+    segments.reverse();
+    let level = segments
+        .iter()
+        .position(|(method, _)| LOG_METHODS.contains(&method.to_lowercase().as_str()))?;
+
+    let (method, arguments) = &segments[level];
+    let mut call = LogCall {
+        function: match &root {
+            Some(root) => format!("{}.{}", root, method),
+            None => method.clone(),
+        },
+        line: node.start_position().row + 1,
+        ..Default::default()
+    };
+    let mut cursor = arguments.walk();
+    let arguments: Vec<Node> = arguments.named_children(&mut cursor).collect();
+    let is_string = |n: &Node| lang.string_kinds().contains(&n.kind());
+    if let Some(first) = arguments.first().filter(|n| is_string(n)) {
+        call.message = Some(unquote(&text(*first)));
+        // printf style methods take format arguments, not fields
+        if !method.ends_with('f') {
+            call.fields
+                .extend(go_attributes(&arguments[1..], source, lang));
+        }
+    }
+    for (i, (method, arguments)) in segments.iter().enumerate() {
+        if i == level {
+            continue;
+        }
+        let mut cursor = arguments.walk();
+        let arguments: Vec<Node> = arguments.named_children(&mut cursor).collect();
+        let first_string = arguments
+            .first()
+            .filter(|n| is_string(n))
+            .map(|n| unquote(&text(*n)));
+        match method.as_str() {
+            "Msg" | "Msgf" if i > level => call.message = first_string,
+            "WithError" | "Err" => call.fields.push("error".to_owned()),
+            "With" | "WithGroup" => call.fields.extend(go_attributes(&arguments, source, lang)),
+            "WithFields" | "Fields" => {
+                for argument in &arguments {
+                    call.fields.extend(go_map_keys(*argument, source, lang));
+                }
+            }
+            method if GO_FIELD_METHODS.contains(&method) => call.fields.extend(first_string),
+            _ => {}
+        }
+    }
+    Some(call)
+}
+
+/// Chained methods taking a field key as their first argument: logrus
+/// `WithField` and the zerolog field methods.
+const GO_FIELD_METHODS: &[&str] = &[
+    "WithField",
+    "Str",
+    "Strs",
+    "Stringer",
+    "Bytes",
+    "Hex",
+    "RawJSON",
+    "AnErr",
+    "Bool",
+    "Bools",
+    "Int",
+    "Int8",
+    "Int16",
+    "Int32",
+    "Int64",
+    "Ints",
+    "Uint",
+    "Uint8",
+    "Uint16",
+    "Uint32",
+    "Uint64",
+    "Float32",
+    "Float64",
+    "Floats64",
+    "Dur",
+    "Time",
+    "TimeDiff",
+    "IPAddr",
+    "MACAddr",
+    "Interface",
+    "Any",
+    "Object",
+    "Dict",
+];
+
+/// Keys of slog/zap style attributes: `"key", value` pairs and
+/// `zap.String("key", v)` / `slog.Int("key", v)` constructors.
+fn go_attributes(arguments: &[Node], source: &[u8], lang: &Language) -> Vec<String> {
+    let text = |n: &Node| n.utf8_text(source).unwrap_or_default().to_owned();
+    let mut keys = vec![];
+    let mut arguments = arguments.iter();
+    while let Some(argument) = arguments.next() {
+        if lang.string_kinds().contains(&argument.kind()) {
+            keys.push(unquote(&text(argument)));
+            // the value
+            arguments.next();
+            continue;
+        }
+        if argument.kind() != "call_expression" {
+            continue;
+        }
+        let Some(function) = argument.child_by_field_name("function") else {
+            continue;
+        };
+        if method_name(&text(&function)) == "error" {
+            keys.push("error".to_owned());
+            continue;
+        }
+        let key = argument
+            .child_by_field_name("arguments")
+            .and_then(|a| a.named_child(0))
+            .filter(|a| lang.string_kinds().contains(&a.kind()));
+        keys.extend(key.map(|key| unquote(&text(&key))));
+    }
+    keys
+}
+
+/// String keys of a map literal such as `logrus.Fields{"order": id}`.
+fn go_map_keys(node: Node, source: &[u8], lang: &Language) -> Vec<String> {
+    let mut keys = vec![];
+    if node.kind() == "keyed_element" {
+        let key = node
+            .child_by_field_name("key")
+            .and_then(|key| key.named_child(0).or(Some(key)))
+            .filter(|key| lang.string_kinds().contains(&key.kind()));
+        if let Some(key) = key {
+            keys.push(unquote(key.utf8_text(source).unwrap_or_default()));
+        }
+        return keys;
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    for child in children {
+        keys.extend(go_map_keys(child, source, lang));
+    }
+    keys
+}
+
+/// Shell scripts log with `logger -p user.warn "..."` or by writing to
+/// stderr: `echo "ERROR: ..." >&2`.
+fn as_shell_call(node: Node, source: &[u8]) -> Option<LogCall> {
+    let text = |n: Node| n.utf8_text(source).unwrap_or_default().to_owned();
+    let line = node.start_position().row + 1;
+    match node.kind() {
+        "command" => {
+            let name = node.child_by_field_name("name")?;
+            if text(name) != "logger" {
+                return None;
+            }
+            let mut cursor = node.walk();
+            let arguments: Vec<String> = node
+                .children_by_field_name("argument", &mut cursor)
+                .map(text)
+                .collect();
+            let mut function = "logger".to_owned();
+            let mut message = None;
+            let mut i = 0;
+            while i < arguments.len() {
+                let argument = &arguments[i];
+                if argument == "-p" || argument == "--priority" {
+                    if let Some(priority) = arguments.get(i + 1) {
+                        function = format!("logger -p {}", priority);
+                    }
+                    i += 2;
+                    continue;
+                }
+                if argument == "-t" || argument == "--tag" {
+                    i += 2;
+                    continue;
+                }
+                if !argument.starts_with('-') {
+                    message = Some(unquote(argument));
+                }
+                i += 1;
+            }
+            Some(LogCall {
+                function,
+                message,
+                line,
+                ..Default::default()
+            })
+        }
+        "redirected_statement" => {
+            let mut cursor = node.walk();
+            let to_stderr = node
+                .children_by_field_name("redirect", &mut cursor)
+                .any(|r| text(r).contains(">&2"));
+            let body = node.child_by_field_name("body")?;
+            let name = text(body.child_by_field_name("name")?);
+            if !to_stderr || !(name == "echo" || name == "printf") {
+                return None;
+            }
+            let mut cursor = body.walk();
+            let message = body
+                .children_by_field_name("argument", &mut cursor)
+                .map(text)
+                .find(|a| !a.starts_with('-'))
+                .map(|a| unquote(&a));
+            Some(LogCall {
+                function: format!("{} >&2", name),
+                message,
+                line,
+                ..Default::default()
+            })
+        }
+        _ => None,
+    }
+}
+
+fn first_string(node: Node, after: usize, source: &[u8], lang: &Language) -> Option<String> {
+    if node.start_byte() >= after && lang.string_kinds().contains(&node.kind()) {
+        return node.utf8_text(source).ok().map(unquote);
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children
+        .into_iter()
+        .filter(|child| child.end_byte() > after)
+        .find_map(|child| first_string(child, after, source, lang))
+}
+
+/// Text of a string literal without prefixes (`@`, `$`, `r#`, `f`...) and
+/// quotes.
+pub fn unquote(literal: &str) -> String {
+    let start = literal.find(['"', '\'', '`']);
+    let Some(start) = start else {
+        return literal.to_owned();
+    };
+    let body = &literal[start..];
+    let quote: String = body
+        .chars()
+        .take_while(|c| *c == body.chars().next().unwrap_or('"'))
+        .take(3)
+        .collect();
+    let quote = if quote.len() == 2 {
+        &quote[..1]
+    } else {
+        &quote
+    };
+    let body = body.strip_prefix(quote).unwrap_or(body);
+    let body = body.trim_end_matches('#');
+    body.strip_suffix(quote).unwrap_or(body).to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn methods() {
+        assert_eq!(method_name("_logger.LogWarning"), "logwarning");
+        assert_eq!(method_name("log::warn!"), "warn");
+        assert_eq!(method_name("this->logger->error"), "error");
+        assert_eq!(method_name("logging.exception"), "exception");
+    }
+
+    #[test]
+    fn levels() {
+        let level = |function: &str| {
+            LogCall {
+                function: function.to_owned(),
+                ..Default::default()
+            }
+            .level()
+        };
+        assert_eq!(level("logger.warning"), Some(Level::Warn));
+        assert_eq!(level("logging.exception"), Some(Level::Fatal));
+        assert_eq!(level("log.critical"), Some(Level::Fatal));
+        assert_eq!(level("logrus.Infof"), Some(Level::Info));
+        assert_eq!(level("_logger.LogWarning"), Some(Level::Warn));
+        assert_eq!(level("fmt.Println"), None);
+        assert_eq!(level("sugar.Debugw"), Some(Level::Debug));
+        assert_eq!(level("sugar.Infow"), Some(Level::Info));
+        assert_eq!(level("sugar.Warnw"), Some(Level::Warn));
+        assert_eq!(level("sugar.Errorw"), Some(Level::Fatal));
+        assert_eq!(level("sugar.Fatalw"), Some(Level::Fatal));
+        assert_eq!(level("fmt.Errorf"), None);
+        assert_eq!(level("errors.Errorf"), None);
+        assert_eq!(level("t.Fatalf"), None);
+        assert_eq!(level("b.Errorf"), None);
+    }
+
+    #[test]
+    fn unquote_literals() {
+        assert_eq!(unquote("\"disk full\""), "disk full");
+        assert_eq!(unquote("@\"C:\\path\""), "C:\\path");
+        assert_eq!(unquote("$\"user {id}\""), "user {id}");
+        assert_eq!(unquote("r#\"raw \"text\"\"#"), "raw \"text\"");
+        assert_eq!(unquote("'''doc'''"), "doc");
+        assert_eq!(unquote("''"), "");
+        assert_eq!(unquote("`tpl ${x}`"), "tpl ${x}");
+    }
+}
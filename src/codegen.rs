@@ -0,0 +1,802 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+};
+
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use crate::{
+    args::{self, CodegenArg},
+    files,
+    meta::Meta,
+    pattern::Pattern,
+};
+
+#[derive(Debug, Serialize)]
+pub struct CodegenData {
+    pub project: String,
+    pub package: String,
+    pub entries: Vec<CodegenEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CodegenEntry {
+    pub name: String,
+    pub id: String,
+    pub level: String,
+    pub message: String,
+    pub subject: String,
+    pub description: String,
+}
+
+impl CodegenData {
+    pub fn new<T>(project: T, package: T, metas: &[Meta], target: &args::Language) -> CodegenData
+    where
+        T: AsRef<str>,
+    {
+        let mut metas = metas.to_vec();
+        Meta::sort(&mut metas);
+        let mut names: HashSet<String> = reserved_names(target, package.as_ref())
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let mut ids: HashMap<String, Meta> = HashMap::new();
+        let mut entries = vec![];
+        for meta in metas {
+            let id = meta.id();
+            if let Some(first) = ids.get(&id) {
+                if first.subject.format() != meta.subject.format()
+                    || first.description.0.trim_end() != meta.description.0.trim_end()
+                {
+                    eprintln!(
+                        "warning: {}:{}: {} message \"{}\" is generated from {}:{} only, its subject or description differs",
+                        meta.file,
+                        meta.line,
+                        meta.level,
+                        meta.message.format(),
+                        first.file,
+                        first.line
+                    );
+                }
+                continue;
+            }
+            ids.insert(id.clone(), meta.clone());
+            let base = const_name(&meta.message.format(), target);
+            let mut name = base.clone();
+            let mut n = 2;
+            while !names.insert(name.clone()) {
+                name = match target {
+                    args::Language::Golang | args::Language::CSharp => format!("{}{}", base, n),
+                    _ => format!("{}_{}", base, n),
+                };
+                n += 1;
+            }
+            entries.push(CodegenEntry {
+                name,
+                id,
+                level: meta.level.to_string(),
+                message: escape(&meta.message.format(), target),
+                subject: escape(&meta.subject.format(), target),
+                description: escape(meta.description.0.trim_end(), target),
+            });
+        }
+        CodegenData {
+            project: project.as_ref().to_owned(),
+            package: package.as_ref().to_owned(),
+            entries,
+        }
+    }
+}
+
+pub fn run(metas: &[Meta], arg: &args::Arg, codegen: &CodegenArg) -> Result<(), Box<dyn Error>> {
+    let target = match &codegen.target {
+        Some(target) => target.clone(),
+        None => arg.language()?,
+    };
+    let package = codegen.package.clone().unwrap_or(package_name(&target));
+    let data = CodegenData::new(arg.project_name(), package.as_str(), metas, &target);
+    let code = render(&data, &target)?;
+    let path = match &codegen.output {
+        Some(output) => output.clone(),
+        None => std::path::Path::new(&arg.save_path)
+            .join(file_name(&target, &package))
+            .display()
+            .to_string(),
+    };
+    files::save(code, &path, arg.dry_run)?;
+    Ok(())
+}
+
+pub fn render(data: &CodegenData, target: &args::Language) -> Result<String, Box<dyn Error>> {
+    let mut reg = Handlebars::new();
+    reg.register_escape_fn(handlebars::no_escape);
+    let result = reg.render_template(&template(target), data)?;
+    Ok(result)
+}
+
+fn package_name(target: &args::Language) -> String {
+    match target {
+        args::Language::Java => "LogMessages".to_owned(),
+        args::Language::Ruby => "LogMessages".to_owned(),
+        args::Language::CSharp => "LogMessages".to_owned(),
+        args::Language::Php => "LogMessages".to_owned(),
+        _ => "logmessages".to_owned(),
+    }
+}
+
+/// File of the generated code; a Java class must live in a file of the
+/// same name.
+fn file_name(target: &args::Language, package: &str) -> String {
+    use args::Language::*;
+    match target {
+        Golang => "log_messages.go",
+        CSharp => "LogMessages.cs",
+        C | Cpp => "log_messages.h",
+        Python => "log_messages.py",
+        Java => return format!("{package}.java"),
+        Kotlin => "LogMessages.kt",
+        JavaScript => "log_messages.js",
+        TypeScript | Tsx => "log_messages.ts",
+        Php => "LogMessages.php",
+        Bash => "log_messages.sh",
+        Lua => "log_messages.lua",
+        Ruby => "log_messages.rb",
+        Rust => "log_messages.rs",
+    }
+    .to_owned()
+}
+
+/// Identifiers the template of the target language declares besides the
+/// message constants.
+fn reserved_names<'a>(target: &args::Language, package: &'a str) -> Vec<&'a str> {
+    use args::Language::*;
+    match target {
+        Rust | Python | JavaScript | Java | Kotlin | Lua | Ruby | Php => vec!["ALL", "BY_ID"],
+        TypeScript | Tsx => vec!["ALL", "BY_ID", "LogMessage"],
+        Golang => vec!["All", "ByID", "LogMessage"],
+        CSharp => vec!["All", "ById", "LogMessage", package],
+        // constants are prefixed, see `const_name`
+        Bash | C | Cpp => vec![],
+    }
+}
+
+/// Constant name for a message: first words of the message in the naming
+/// convention of the target language. Bash names get a `LOGDOC_` prefix so
+/// a sourced file can't overwrite variables like `PATH` or `HOME`.
+fn const_name(message: &str, target: &args::Language) -> String {
+    let name = plain_const_name(message, target);
+    match target {
+        args::Language::Bash => format!("LOGDOC_{name}"),
+        _ => name,
+    }
+}
+
+fn plain_const_name(message: &str, target: &args::Language) -> String {
+    let mut words = Pattern::from(message).words();
+    words.truncate(6);
+    let name = match target {
+        args::Language::Golang | args::Language::CSharp => camel_case(&words),
+        _ => words.join("_").to_uppercase(),
+    };
+    match name.chars().next() {
+        None => match target {
+            args::Language::Golang | args::Language::CSharp => "Message".to_owned(),
+            _ => "MESSAGE".to_owned(),
+        },
+        Some(c) if c.is_ascii_digit() => match target {
+            args::Language::Golang | args::Language::CSharp => format!("Msg{}", name),
+            _ => format!("MSG_{}", name),
+        },
+        Some(_) => name,
+    }
+}
+
+pub(crate) fn camel_case<T: AsRef<str>>(words: &[T]) -> String {
+    words
+        .iter()
+        .map(|w| {
+            let mut c = w.as_ref().chars();
+            match c.next() {
+                Some(f) => f.to_ascii_uppercase().to_string() + c.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Body of a double quoted string literal of the target language.
+fn escape(text: &str, target: &args::Language) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '#' if *target == args::Language::Ruby && chars.peek() == Some(&'{') => {
+                out.push_str("\\#")
+            }
+            '$' if matches!(
+                target,
+                args::Language::JavaScript | args::Language::TypeScript | args::Language::Tsx
+            ) && chars.peek() == Some(&'{') =>
+            {
+                out.push_str("\\$")
+            }
+            '$' if matches!(
+                target,
+                args::Language::Php | args::Language::Bash | args::Language::Kotlin
+            ) =>
+            {
+                out.push_str("\\$")
+            }
+            '`' if *target == args::Language::Bash => out.push_str("\\`"),
+            c if c.is_control() => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn template(target: &args::Language) -> String {
+    use args::Language::*;
+    match target {
+        Rust => String::from(
+            r#"// Code generated by logdoc for {{ project }}. DO NOT EDIT.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogMessage {
+    pub id: &'static str,
+    pub level: &'static str,
+    pub message: &'static str,
+    pub subject: &'static str,
+    pub description: &'static str,
+}
+{{#each entries as |e|}}
+
+pub const {{e.name}}: LogMessage = LogMessage {
+    id: "{{e.id}}",
+    level: "{{e.level}}",
+    message: "{{e.message}}",
+    subject: "{{e.subject}}",
+    description: "{{e.description}}",
+};
+{{/each}}
+
+pub const ALL: &[LogMessage] = &[
+{{#each entries as |e|}}
+    {{e.name}},
+{{/each}}
+];
+
+pub fn by_id(id: &str) -> Option<&'static LogMessage> {
+    ALL.iter().find(|m| m.id == id)
+}
+"#,
+        ),
+        Golang => String::from(
+            r#"// Code generated by logdoc for {{ project }}. DO NOT EDIT.
+
+package {{ package }}
+
+type LogMessage struct {
+	ID          string
+	Level       string
+	Message     string
+	Subject     string
+	Description string
+}
+
+var (
+{{#each entries as |e|}}
+	{{e.name}} = LogMessage{
+		ID:          "{{e.id}}",
+		Level:       "{{e.level}}",
+		Message:     "{{e.message}}",
+		Subject:     "{{e.subject}}",
+		Description: "{{e.description}}",
+	}
+{{/each}}
+)
+
+var All = []LogMessage{
+{{#each entries as |e|}}
+	{{e.name}},
+{{/each}}
+}
+
+func ByID(id string) (LogMessage, bool) {
+	for _, m := range All {
+		if m.ID == id {
+			return m, true
+		}
+	}
+	return LogMessage{}, false
+}
+"#,
+        ),
+        Python => String::from(
+            r#"# Code generated by logdoc for {{ project }}. DO NOT EDIT.
+
+from dataclasses import dataclass
+
+
+@dataclass(frozen=True)
+class LogMessage:
+    id: str
+    level: str
+    message: str
+    subject: str
+    description: str
+
+{{#each entries as |e|}}
+
+{{e.name}} = LogMessage(
+    id="{{e.id}}",
+    level="{{e.level}}",
+    message="{{e.message}}",
+    subject="{{e.subject}}",
+    description="{{e.description}}",
+)
+{{/each}}
+
+ALL = (
+{{#each entries as |e|}}
+    {{e.name}},
+{{/each}}
+)
+
+BY_ID = {m.id: m for m in ALL}
+"#,
+        ),
+        JavaScript => String::from(
+            r#"// Code generated by logdoc for {{ project }}. DO NOT EDIT.
+{{#each entries as |e|}}
+
+export const {{e.name}} = Object.freeze({
+  id: "{{e.id}}",
+  level: "{{e.level}}",
+  message: "{{e.message}}",
+  subject: "{{e.subject}}",
+  description: "{{e.description}}",
+});
+{{/each}}
+
+export const ALL = Object.freeze([
+{{#each entries as |e|}}
+  {{e.name}},
+{{/each}}
+]);
+
+export function byId(id) {
+  return ALL.find((m) => m.id === id);
+}
+"#,
+        ),
+        TypeScript | Tsx => String::from(
+            r#"// Code generated by logdoc for {{ project }}. DO NOT EDIT.
+
+export interface LogMessage {
+  readonly id: string;
+  readonly level: string;
+  readonly message: string;
+  readonly subject: string;
+  readonly description: string;
+}
+{{#each entries as |e|}}
+
+export const {{e.name}}: LogMessage = Object.freeze({
+  id: "{{e.id}}",
+  level: "{{e.level}}",
+  message: "{{e.message}}",
+  subject: "{{e.subject}}",
+  description: "{{e.description}}",
+});
+{{/each}}
+
+export const ALL: readonly LogMessage[] = Object.freeze([
+{{#each entries as |e|}}
+  {{e.name}},
+{{/each}}
+]);
+
+export function byId(id: string): LogMessage | undefined {
+  return ALL.find((m) => m.id === id);
+}
+"#,
+        ),
+        Java => String::from(
+            r#"// Code generated by logdoc for {{ project }}. DO NOT EDIT.
+
+import java.util.List;
+import java.util.Optional;
+
+public final class {{ package }} {
+    public record LogMessage(String id, String level, String message, String subject, String description) {}
+
+    private {{ package }}() {}
+{{#each entries as |e|}}
+
+    public static final LogMessage {{e.name}} = new LogMessage(
+        "{{e.id}}",
+        "{{e.level}}",
+        "{{e.message}}",
+        "{{e.subject}}",
+        "{{e.description}}");
+{{/each}}
+
+    public static final List<LogMessage> ALL = List.of(
+{{#each entries as |e|}}
+        {{e.name}}{{#unless @last}},{{/unless}}
+{{/each}}
+    );
+
+    public static Optional<LogMessage> byId(String id) {
+        return ALL.stream().filter(m -> m.id().equals(id)).findFirst();
+    }
+}
+"#,
+        ),
+        Kotlin => String::from(
+            r#"// Code generated by logdoc for {{ project }}. DO NOT EDIT.
+
+package {{ package }}
+
+data class LogMessage(
+    val id: String,
+    val level: String,
+    val message: String,
+    val subject: String,
+    val description: String,
+)
+{{#each entries as |e|}}
+
+val {{e.name}} = LogMessage(
+    id = "{{e.id}}",
+    level = "{{e.level}}",
+    message = "{{e.message}}",
+    subject = "{{e.subject}}",
+    description = "{{e.description}}",
+)
+{{/each}}
+
+val ALL: List<LogMessage> = listOf(
+{{#each entries as |e|}}
+    {{e.name}},
+{{/each}}
+)
+
+fun byId(id: String): LogMessage? = ALL.find { it.id == id }
+"#,
+        ),
+        CSharp => String::from(
+            r#"// Code generated by logdoc for {{ project }}. DO NOT EDIT.
+
+using System.Collections.Generic;
+using System.Linq;
+
+public static class {{ package }}
+{
+    public sealed record LogMessage(string Id, string Level, string Message, string Subject, string Description);
+{{#each entries as |e|}}
+
+    public static readonly LogMessage {{e.name}} = new(
+        "{{e.id}}",
+        "{{e.level}}",
+        "{{e.message}}",
+        "{{e.subject}}",
+        "{{e.description}}");
+{{/each}}
+
+    public static readonly IReadOnlyList<LogMessage> All = new[]
+    {
+{{#each entries as |e|}}
+        {{e.name}},
+{{/each}}
+    };
+
+    public static LogMessage? ById(string id) => All.FirstOrDefault(m => m.Id == id);
+}
+"#,
+        ),
+        Ruby => String::from(
+            r#"# Code generated by logdoc for {{ project }}. DO NOT EDIT.
+
+module {{ package }}
+  LogMessage = Struct.new(:id, :level, :message, :subject, :description)
+{{#each entries as |e|}}
+
+  {{e.name}} = LogMessage.new(
+    "{{e.id}}",
+    "{{e.level}}",
+    "{{e.message}}",
+    "{{e.subject}}",
+    "{{e.description}}"
+  ).freeze
+{{/each}}
+
+  ALL = [
+{{#each entries as |e|}}
+    {{e.name}},
+{{/each}}
+  ].freeze
+
+  def self.by_id(id)
+    ALL.find { |m| m.id == id }
+  end
+end
+"#,
+        ),
+        Php => String::from(
+            r#"<?php
+// Code generated by logdoc for {{ project }}. DO NOT EDIT.
+
+final class {{ package }}
+{
+{{#each entries as |e|}}
+    public const {{e.name}} = [
+        'id' => "{{e.id}}",
+        'level' => "{{e.level}}",
+        'message' => "{{e.message}}",
+        'subject' => "{{e.subject}}",
+        'description' => "{{e.description}}",
+    ];
+
+{{/each}}
+    public const ALL = [
+{{#each entries as |e|}}
+        self::{{e.name}},
+{{/each}}
+    ];
+
+    public static function byId(string $id): ?array
+    {
+        foreach (self::ALL as $message) {
+            if ($message['id'] === $id) {
+                return $message;
+            }
+        }
+        return null;
+    }
+}
+"#,
+        ),
+        Lua => String::from(
+            r#"-- Code generated by logdoc for {{ project }}. DO NOT EDIT.
+
+local M = {}
+{{#each entries as |e|}}
+
+M.{{e.name}} = {
+  id = "{{e.id}}",
+  level = "{{e.level}}",
+  message = "{{e.message}}",
+  subject = "{{e.subject}}",
+  description = "{{e.description}}",
+}
+{{/each}}
+
+M.ALL = {
+{{#each entries as |e|}}
+  M.{{e.name}},
+{{/each}}
+}
+
+function M.by_id(id)
+  for _, m in ipairs(M.ALL) do
+    if m.id == id then
+      return m
+    end
+  end
+  return nil
+end
+
+return M
+"#,
+        ),
+        Bash => String::from(
+            r#"# Code generated by logdoc for {{ project }}. DO NOT EDIT.
+# Source this file; messages are keyed by their ID.
+
+declare -A LOG_MESSAGE_LEVEL=(
+{{#each entries as |e|}}
+    ["{{e.id}}"]="{{e.level}}"
+{{/each}}
+)
+
+declare -A LOG_MESSAGE_TEXT=(
+{{#each entries as |e|}}
+    ["{{e.id}}"]="{{e.message}}"
+{{/each}}
+)
+
+declare -A LOG_MESSAGE_SUBJECT=(
+{{#each entries as |e|}}
+    ["{{e.id}}"]="{{e.subject}}"
+{{/each}}
+)
+
+declare -A LOG_MESSAGE_DESCRIPTION=(
+{{#each entries as |e|}}
+    ["{{e.id}}"]="{{e.description}}"
+{{/each}}
+)
+{{#each entries as |e|}}
+
+{{e.name}}="{{e.id}}"
+{{/each}}
+"#,
+        ),
+        C | Cpp => String::from(
+            r#"/* Code generated by logdoc for {{ project }}. DO NOT EDIT. */
+
+#ifndef LOGDOC_LOG_MESSAGES_H
+#define LOGDOC_LOG_MESSAGES_H
+
+#include <stddef.h>
+#include <string.h>
+
+typedef struct {
+    const char *id;
+    const char *level;
+    const char *message;
+    const char *subject;
+    const char *description;
+} log_message;
+
+static const log_message LOG_MESSAGES[] = {
+{{#each entries as |e|}}
+    /* {{e.name}} */
+    {"{{e.id}}", "{{e.level}}", "{{e.message}}", "{{e.subject}}", "{{e.description}}"},
+{{/each}}
+};
+
+static const size_t LOG_MESSAGES_LEN = sizeof(LOG_MESSAGES) / sizeof(LOG_MESSAGES[0]);
+
+static inline const log_message *log_message_by_id(const char *id) {
+    for (size_t i = 0; i < LOG_MESSAGES_LEN; i++) {
+        if (strcmp(LOG_MESSAGES[i].id, id) == 0) {
+            return &LOG_MESSAGES[i];
+        }
+    }
+    return NULL;
+}
+
+#endif
+"#,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::meta::{Description, Level, Message, Subject};
+
+    use super::*;
+
+    fn metas() -> Vec<Meta> {
+        vec![
+            Meta {
+                level: Level::Warn,
+                message: Message("connection \"%s\" lost".to_owned()),
+                subject: Subject("database is unreachable\n".to_owned()),
+                description: Description("check the network\ncall admin\n".to_owned()),
+                file: "main.go".to_owned(),
+                line: 12,
+                ..Default::default()
+            },
+            Meta {
+                level: Level::Info,
+                message: Message("service started".to_owned()),
+                subject: Subject("ready\n".to_owned()),
+                file: "main.go".to_owned(),
+                line: 3,
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            const_name("connection %s lost", &args::Language::Rust),
+            "CONNECTION_LOST"
+        );
+        assert_eq!(
+            const_name("connection %s lost", &args::Language::Golang),
+            "ConnectionLost"
+        );
+        assert_eq!(
+            const_name("404 page", &args::Language::Python),
+            "MSG_404_PAGE"
+        );
+        assert_eq!(const_name("", &args::Language::Rust), "MESSAGE");
+    }
+
+    #[test]
+    fn render_rust() {
+        let data = CodegenData::new("project", "logmessages", &metas(), &args::Language::Rust);
+        assert_eq!(data.entries[0].name, "SERVICE_STARTED");
+        assert_eq!(data.entries[1].name, "CONNECTION_LOST");
+        let code = render(&data, &args::Language::Rust).unwrap();
+        assert!(code.contains("pub const CONNECTION_LOST: LogMessage = LogMessage {\n"));
+        assert!(code.contains("    message: \"connection \\\"%s\\\" lost\",\n"));
+        assert!(code.contains("    description: \"check the network\\ncall admin\",\n"));
+        assert!(code.contains("    SERVICE_STARTED,\n    CONNECTION_LOST,\n"));
+    }
+
+    #[test]
+    fn reserved() {
+        let mut metas = metas();
+        metas[0].message = Message("all".to_owned());
+        metas[1].message = Message("log message".to_owned());
+        let data = CodegenData::new("p", "pkg", &metas, &args::Language::Python);
+        let names: Vec<&str> = data.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["LOG_MESSAGE", "ALL_2"]);
+        let data = CodegenData::new("p", "pkg", &metas, &args::Language::Golang);
+        let names: Vec<&str> = data.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["LogMessage2", "All2"]);
+    }
+
+    #[test]
+    fn render_typescript() {
+        let data = CodegenData::new("p", "logmessages", &metas(), &args::Language::TypeScript);
+        let code = render(&data, &args::Language::TypeScript).unwrap();
+        assert!(code.contains("export interface LogMessage {\n  readonly id: string;\n"));
+        assert!(code.contains("export const SERVICE_STARTED: LogMessage = Object.freeze({\n"));
+        assert!(code.contains("export function byId(id: string): LogMessage | undefined {\n"));
+    }
+
+    #[test]
+    fn render_bash() {
+        let mut metas = metas();
+        metas[1].message = Message("path".to_owned());
+        let data = CodegenData::new("p", "logmessages", &metas, &args::Language::Bash);
+        let code = render(&data, &args::Language::Bash).unwrap();
+        assert!(code.contains("\nLOGDOC_PATH=\""));
+        assert!(!code.contains("\nPATH="));
+    }
+
+    #[test]
+    fn java_file_name() {
+        assert_eq!(file_name(&args::Language::Java, "Foo"), "Foo.java");
+        let data = CodegenData::new("p", "Foo", &metas(), &args::Language::Java);
+        let code = render(&data, &args::Language::Java).unwrap();
+        assert!(code.contains("public final class Foo {\n"));
+    }
+
+    #[test]
+    fn render_kotlin() {
+        let mut metas = metas();
+        metas[1].message = Message("price is ${amount}".to_owned());
+        let data = CodegenData::new("p", "logmessages", &metas, &args::Language::Kotlin);
+        let code = render(&data, &args::Language::Kotlin).unwrap();
+        assert!(code.contains("package logmessages\n"));
+        assert!(code.contains("val PRICE_IS = LogMessage(\n"));
+        assert!(code.contains("    message = \"price is \\${amount}\",\n"));
+        assert!(code.contains("val ALL: List<LogMessage> = listOf(\n"));
+    }
+
+    #[test]
+    fn render_lua() {
+        let data = CodegenData::new("p", "logmessages", &metas(), &args::Language::Lua);
+        let code = render(&data, &args::Language::Lua).unwrap();
+        assert!(code.contains("M.CONNECTION_LOST = {\n  id = \""));
+        assert!(code.contains("M.ALL = {\n  M.SERVICE_STARTED,\n  M.CONNECTION_LOST,\n}\n"));
+        assert!(code.ends_with("return M\n"));
+    }
+
+    #[test]
+    fn render_is_deterministic() {
+        let mut reversed = metas();
+        reversed.reverse();
+        for target in [args::Language::Golang, args::Language::Python] {
+            let a = render(&CodegenData::new("p", "pkg", &metas(), &target), &target).unwrap();
+            let b = render(&CodegenData::new("p", "pkg", &reversed, &target), &target).unwrap();
+            assert_eq!(a, b);
+        }
+    }
+}
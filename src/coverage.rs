@@ -0,0 +1,330 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt::Write,
+    path::Path,
+};
+
+use serde::Serialize;
+
+use crate::{
+    app::Scan,
+    args::{self, CoverageArg, CoverageFormat},
+    calls::LogCall,
+    files,
+    meta::{Level, Meta},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Counts {
+    pub calls: usize,
+    pub documented: usize,
+}
+
+impl Counts {
+    /// Documented calls in percent, 100 when there are no calls.
+    pub fn percent(&self) -> f64 {
+        if self.calls == 0 {
+            return 100.0;
+        }
+        self.documented as f64 * 100.0 / self.calls as f64
+    }
+
+    fn add(&mut self, documented: bool) {
+        self.calls += 1;
+        if documented {
+            self.documented += 1;
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Undocumented {
+    pub file: String,
+    pub line: usize,
+    pub function: String,
+    pub message: Option<String>,
+    pub level: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub total: Counts,
+    pub levels: BTreeMap<String, Counts>,
+    pub directories: BTreeMap<String, Counts>,
+    pub files: BTreeMap<String, Counts>,
+    pub undocumented: Vec<Undocumented>,
+}
+
+pub fn run(scan: &Scan, arg: &args::Arg, coverage: &CoverageArg) -> Result<(), Box<dyn Error>> {
+    let report = Report::new(&scan.metas, &scan.calls);
+
+    let data = match coverage.format {
+        CoverageFormat::Text => report.text(),
+        CoverageFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+        CoverageFormat::Markdown => report.markdown(),
+    };
+    match &coverage.output {
+        Some(output) => {
+            files::save(data, output, arg.dry_run)?;
+        }
+        None => print!("{data}"),
+    }
+    report.check(&coverage.min_coverage)
+}
+
+impl Report {
+    /// Coverage of `calls`: a call is documented when an annotation links to
+    /// it, merged duplicates included. Calls of unknown level (`print`,
+    /// `log`, ...) count only when documented, at the level of their
+    /// annotation.
+    pub fn new(metas: &[Meta], calls: &[(String, LogCall)]) -> Report {
+        let documented: HashMap<(&str, usize), Level> = metas
+            .iter()
+            .flat_map(|m| {
+                let merged = m
+                    .locations
+                    .iter()
+                    .map(|(file, _, call)| (file.as_str(), call));
+                std::iter::once((m.file.as_str(), &m.call))
+                    .chain(merged)
+                    .filter_map(|(file, call)| {
+                        call.as_ref().map(|call| ((file, call.line), m.level))
+                    })
+            })
+            .collect();
+        let mut report = Report::default();
+        for level in Level::all() {
+            report.levels.insert(level.to_string(), Counts::default());
+        }
+        for (file, call) in calls {
+            let meta_level = documented.get(&(file.as_str(), call.line)).copied();
+            let Some(level) = meta_level.or(call.level()) else {
+                continue;
+            };
+            let is_documented = meta_level.is_some();
+            let dir = match Path::new(file).parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir.display().to_string(),
+                _ => ".".to_owned(),
+            };
+            report.total.add(is_documented);
+            report
+                .levels
+                .entry(level.to_string())
+                .or_default()
+                .add(is_documented);
+            report
+                .directories
+                .entry(dir)
+                .or_default()
+                .add(is_documented);
+            report
+                .files
+                .entry(file.clone())
+                .or_default()
+                .add(is_documented);
+            if !is_documented {
+                report.undocumented.push(Undocumented {
+                    file: file.clone(),
+                    line: call.line,
+                    function: call.function.clone(),
+                    message: call.message.clone(),
+                    level: level.to_string(),
+                });
+            }
+        }
+        report
+    }
+
+    /// Fail when coverage is below one of the thresholds.
+    pub fn check(&self, thresholds: &[args::MinCoverage]) -> Result<(), Box<dyn Error>> {
+        let mut failed = vec![];
+        for threshold in thresholds {
+            let (name, counts) = match &threshold.level {
+                Some(level) => (
+                    level.to_string(),
+                    self.levels
+                        .get(&level.to_string())
+                        .copied()
+                        .unwrap_or_default(),
+                ),
+                None => ("total".to_owned(), self.total),
+            };
+            if counts.percent() < threshold.percent {
+                failed.push(format!(
+                    "{name} {:.1}% < {}%",
+                    counts.percent(),
+                    threshold.percent
+                ));
+            }
+        }
+        if failed.is_empty() {
+            return Ok(());
+        }
+        Err(format!("coverage below minimum: {}", failed.join(", ")).into())
+    }
+
+    pub fn text(&self) -> String {
+        let mut out = format!(
+            "coverage: {}/{} ({:.1}%)\n",
+            self.total.documented,
+            self.total.calls,
+            self.total.percent()
+        );
+        for (title, rows) in self.sections() {
+            let width = rows
+                .keys()
+                .map(|k| k.len())
+                .max()
+                .unwrap_or(0)
+                .max(title.len());
+            let _ = writeln!(out, "\n{title:width$}  calls  documented  coverage");
+            for (name, counts) in rows {
+                let _ = writeln!(
+                    out,
+                    "{name:width$}  {:>5}  {:>10}  {:>7.1}%",
+                    counts.calls,
+                    counts.documented,
+                    counts.percent()
+                );
+            }
+        }
+        if !self.undocumented.is_empty() {
+            out.push_str("\nundocumented\n");
+            for call in &self.undocumented {
+                let _ = writeln!(
+                    out,
+                    "{}:{} {} {}",
+                    call.file, call.line, call.level, call.function
+                );
+            }
+        }
+        out
+    }
+
+    pub fn markdown(&self) -> String {
+        let percent = self.total.percent();
+        let color = match percent {
+            p if p >= 90.0 => "brightgreen",
+            p if p >= 75.0 => "yellow",
+            p if p >= 50.0 => "orange",
+            _ => "red",
+        };
+        let mut out = format!(
+            "![log docs](https://img.shields.io/badge/log%20docs-{percent:.0}%25-{color})\n\n\
+             **{}/{}** log calls documented ({percent:.1}%)\n",
+            self.total.documented, self.total.calls
+        );
+        for (title, rows) in self.sections() {
+            let _ = write!(
+                out,
+                "\n|{title}|calls|documented|coverage|\n|---|---|---|---|\n"
+            );
+            for (name, counts) in rows {
+                let _ = writeln!(
+                    out,
+                    "|{name}|{}|{}|{:.1}%|",
+                    counts.calls,
+                    counts.documented,
+                    counts.percent()
+                );
+            }
+        }
+        out
+    }
+
+    fn sections(&self) -> [(&'static str, &BTreeMap<String, Counts>); 3] {
+        [
+            ("level", &self.levels),
+            ("directory", &self.directories),
+            ("file", &self.files),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::Application,
+        args::MinCoverage,
+        calls::LogCall,
+        language::Language,
+        meta::{Level, Meta},
+    };
+
+    use super::Report;
+
+    #[test]
+    fn coverage() {
+        let call = |function: &str, line| LogCall {
+            function: function.to_owned(),
+            line,
+            ..Default::default()
+        };
+        let metas = vec![Meta {
+            level: Level::Warn,
+            file: "src/main.go".to_owned(),
+            call: Some(call("log.Warn", 4)),
+            locations: vec![("src/main.go".to_owned(), 11, Some(call("log.Warn", 12)))],
+            ..Default::default()
+        }];
+        let calls = vec![
+            ("src/main.go".to_owned(), call("log.Warn", 4)),
+            ("src/main.go".to_owned(), call("log.Warn", 12)),
+            ("src/main.go".to_owned(), call("log.Warnf", 9)),
+            ("src/db/pool.go".to_owned(), call("log.Fatal", 3)),
+            ("src/db/pool.go".to_owned(), call("fmt.Println", 5)),
+        ];
+        let report = Report::new(&metas, &calls);
+        assert_eq!((report.total.calls, report.total.documented), (4, 2));
+        assert_eq!(report.levels["warn"].percent(), 200.0 / 3.0);
+        assert_eq!(report.directories["src/db"].calls, 1);
+        assert_eq!(report.undocumented.len(), 2);
+
+        let warn = MinCoverage {
+            level: Some(Level::Warn),
+            percent: 50.0,
+        };
+        assert!(report.check(std::slice::from_ref(&warn)).is_ok());
+        let fatal = MinCoverage {
+            level: Some(Level::Fatal),
+            percent: 100.0,
+        };
+        assert_eq!(
+            report.check(&[warn, fatal]).unwrap_err().to_string(),
+            "coverage below minimum: fatal 0.0% < 100%"
+        );
+        assert!(report
+            .markdown()
+            .starts_with("![log docs](https://img.shields.io/badge/log%20docs-50%25-orange)\n"));
+    }
+
+    #[test]
+    fn find_all_calls() {
+        let lang = Language::Golang;
+        let file = "tests/go/main.go".to_owned();
+        let metas = Application::collect_files(&lang, std::slice::from_ref(&file)).unwrap();
+        let source = std::fs::read_to_string(&file).unwrap();
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&lang.sitter_language()).unwrap();
+        let tree = parser.parse(source.as_bytes(), None).unwrap();
+        let calls: Vec<(String, LogCall)> =
+            crate::calls::find_all(tree.root_node(), source.as_bytes(), &lang)
+                .into_iter()
+                .map(|call| (file.clone(), call))
+                .collect();
+        // fmt.Errorf in open() builds an error, it isn't a log call
+        assert_eq!(calls.len(), 7);
+        let report = Report::new(&metas, &calls);
+        assert_eq!((report.total.calls, report.total.documented), (6, 3));
+        assert_eq!(
+            (
+                report.levels["info"].calls,
+                report.levels["info"].documented
+            ),
+            (3, 2)
+        );
+        let undocumented: Vec<usize> = report.undocumented.iter().map(|u| u.line).collect();
+        assert_eq!(undocumented, [16, 18, 22]);
+    }
+}
@@ -0,0 +1,272 @@
+use std::{collections::BTreeMap, error::Error, fmt::Display};
+
+use crate::{
+    args::Duplicates,
+    meta::{Level, Meta},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum Kind {
+    /// Same level, message, subject and description
+    Identical,
+    /// Same level and message, different subject or description
+    Conflicting,
+    /// Same level, messages equal after normalization
+    Near,
+    /// Same message at different levels
+    Level,
+}
+
+impl Kind {
+    /// Rule id of findings of this kind.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            Kind::Identical => "duplicate",
+            Kind::Conflicting => "conflicting-duplicate",
+            Kind::Near => "near-duplicate",
+            Kind::Level => "level-conflict",
+        }
+    }
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            Kind::Identical => "duplicate",
+            Kind::Conflicting => "conflicting",
+            Kind::Near => "near-duplicate",
+            Kind::Level => "different levels for",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Duplicate {
+    pub kind: Kind,
+    pub message: String,
+    /// Every entry involved
+    pub locations: Vec<Location>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    pub level: Level,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{} {}", self.file, self.line, self.level)
+    }
+}
+
+impl Display for Duplicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} message \"{}\" at {}",
+            self.kind,
+            self.message,
+            self.locations
+                .iter()
+                .map(|l| l.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Report duplicates according to `mode`: print them, merge identical
+/// entries or fail.
+pub fn check(metas: Vec<Meta>, mode: &Duplicates) -> Result<Vec<Meta>, Box<dyn Error>> {
+    if *mode == Duplicates::Ignore {
+        return Ok(metas);
+    }
+    let metas = if *mode == Duplicates::Merge {
+        merge(metas)
+    } else {
+        metas
+    };
+    let duplicates = find(&metas);
+    for duplicate in &duplicates {
+        eprintln!("warning: {duplicate}");
+    }
+    if *mode == Duplicates::Fail && !duplicates.is_empty() {
+        return Err(format!("{} duplicate messages", duplicates.len()).into());
+    }
+    Ok(metas)
+}
+
+/// Duplicated messages, identical entries first.
+pub fn find(metas: &[Meta]) -> Vec<Duplicate> {
+    let mut by_normalized: BTreeMap<String, Vec<&Meta>> = BTreeMap::new();
+    for meta in metas {
+        by_normalized
+            .entry(normalize(&meta.message.format()))
+            .or_default()
+            .push(meta);
+    }
+    let mut duplicates = vec![];
+    for same in by_normalized.values().filter(|same| same.len() > 1) {
+        let kind = if same.iter().any(|m| m.level != same[0].level) {
+            Kind::Level
+        } else if same.iter().any(|m| m.message != same[0].message) {
+            Kind::Near
+        } else if same
+            .iter()
+            .any(|m| m.subject != same[0].subject || m.description != same[0].description)
+        {
+            Kind::Conflicting
+        } else {
+            Kind::Identical
+        };
+        duplicates.push(Duplicate {
+            kind,
+            message: same[0].message.format(),
+            locations: same
+                .iter()
+                .map(|m| Location {
+                    file: m.file.clone(),
+                    line: m.line,
+                    level: m.level,
+                })
+                .collect(),
+        });
+    }
+    duplicates.sort_by_key(|d| d.kind);
+    duplicates
+}
+
+/// Merge identical entries into the first one, keeping the locations of
+/// the others.
+pub fn merge(metas: Vec<Meta>) -> Vec<Meta> {
+    let mut merged: Vec<Meta> = vec![];
+    for meta in metas {
+        let first = merged.iter_mut().find(|m| {
+            m.level == meta.level
+                && m.message == meta.message
+                && m.subject == meta.subject
+                && m.description == meta.description
+        });
+        match first {
+            Some(first) => first.locations.push((meta.file, meta.line, meta.call)),
+            None => merged.push(meta),
+        }
+    }
+    merged
+}
+
+/// Message compared for near-duplicates: lowercase, whitespace collapsed
+/// and `%s`, `{}`, `{name}`, `$name` and `${name}` placeholders replaced by `{}`.
+pub fn normalize(message: &str) -> String {
+    let mut normalized = String::with_capacity(message.len());
+    let mut chars = message.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' if chars.peek() == Some(&'%') => {
+                chars.next();
+                normalized.push('%');
+            }
+            '%' if chars
+                .peek()
+                .is_some_and(|c| c.is_alphanumeric() || "+-#.".contains(*c)) =>
+            {
+                for c in chars.by_ref() {
+                    if c.is_alphabetic() {
+                        break;
+                    }
+                }
+                normalized.push_str("{}");
+            }
+            '{' => {
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                }
+                normalized.push_str("{}");
+            }
+            '$' if chars
+                .peek()
+                .is_some_and(|c| c.is_alphanumeric() || *c == '_' || *c == '{') =>
+            {
+                if chars.peek() == Some(&'{') {
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            break;
+                        }
+                    }
+                } else {
+                    while chars
+                        .peek()
+                        .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+                    {
+                        chars.next();
+                    }
+                }
+                normalized.push_str("{}");
+            }
+            _ => normalized.extend(c.to_lowercase()),
+        }
+    }
+    normalized.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::meta::{Description, Level, Message, Meta, Subject};
+
+    use super::{find, merge, normalize, Kind};
+
+    fn meta(level: Level, message: &str, subject: &str, line: usize) -> Meta {
+        Meta {
+            level,
+            message: Message(message.to_owned()),
+            subject: Subject(subject.to_owned()),
+            description: Description::default(),
+            file: "main.go".to_owned(),
+            line,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn normalize_messages() {
+        assert_eq!(normalize("User %s  not Found"), "user {} not found");
+        assert_eq!(normalize("user {id} not found"), "user {} not found");
+        assert_eq!(
+            normalize("user ${id} not found: $err"),
+            "user {} not found: {}"
+        );
+        assert_eq!(normalize("loaded %5.2f%% of %d"), "loaded {}% of {}");
+    }
+
+    #[test]
+    fn find_duplicates() {
+        let metas = vec![
+            meta(Level::Info, "started", "server", 1),
+            meta(Level::Info, "started", "server", 5),
+            meta(Level::Warn, "retry %d", "db", 7),
+            meta(Level::Warn, "Retry {}", "db", 9),
+            meta(Level::Info, "stopped", "server", 11),
+            meta(Level::Info, "stopped", "worker", 12),
+            meta(Level::Fatal, "no config", "", 13),
+            meta(Level::Warn, "no config", "", 14),
+        ];
+        let kinds: Vec<Kind> = find(&metas).iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            [Kind::Identical, Kind::Conflicting, Kind::Near, Kind::Level]
+        );
+        assert_eq!(
+            find(&metas)[0].to_string(),
+            "duplicate message \"started\" at main.go:1 info, main.go:5 info"
+        );
+
+        let merged = merge(metas);
+        assert_eq!(merged.len(), 7);
+        assert_eq!(merged[0].locations, [("main.go".to_owned(), 5, None)]);
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    io::{BufRead, Write},
+};
+
+use crate::{
+    args::{ExplainArg, InputFormat},
+    meta::Meta,
+    pattern::Pattern,
+};
+
+/// Documented messages prepared for matching against log lines.
+pub struct Catalog<'m> {
+    entries: Vec<(Pattern, &'m Meta)>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub total: usize,
+    pub matched: usize,
+    pub unmatched: BTreeMap<String, usize>,
+}
+
+impl<'m> Catalog<'m> {
+    /// Patterns come from the documented message and from the format string
+    /// of the linked logging call.
+    pub fn new(metas: &'m [Meta]) -> Self {
+        let mut entries: Vec<(Pattern, &Meta)> = metas
+            .iter()
+            .flat_map(|m| {
+                let call_message = m.call.as_ref().and_then(|c| c.message.as_ref());
+                std::iter::once(Pattern::from(&m.message.format()))
+                    .chain(call_message.map(Pattern::from))
+                    .map(move |p| (p, m))
+            })
+            .filter(|(p, _)| !p.is_empty())
+            .collect();
+        entries.sort_by_key(|(p, _)| std::cmp::Reverse(p.specificity()));
+        Catalog { entries }
+    }
+
+    /// Most specific entry documenting `message`. When `whole` is false the
+    /// message may be surrounded by other text (timestamps, levels...).
+    pub fn lookup(&self, message: &str, whole: bool) -> Option<&'m Meta> {
+        self.entries
+            .iter()
+            .find(|(p, _)| {
+                if whole {
+                    p.is_match(message)
+                } else {
+                    p.is_contained_in(message)
+                }
+            })
+            .map(|(_, m)| *m)
+    }
+}
+
+pub fn run(metas: &[Meta], arg: &ExplainArg) -> Result<(), Box<dyn Error>> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let summary = match &arg.input {
+        Some(path) => {
+            let file = std::fs::File::open(path)?;
+            explain(metas, std::io::BufReader::new(file), &mut out, arg)?
+        }
+        None => explain(metas, std::io::stdin().lock(), &mut out, arg)?,
+    };
+    write_summary(&mut out, &summary)?;
+    Ok(())
+}
+
+pub fn explain<R, W>(
+    metas: &[Meta],
+    input: R,
+    out: &mut W,
+    arg: &ExplainArg,
+) -> Result<Summary, Box<dyn Error>>
+where
+    R: BufRead,
+    W: Write,
+{
+    let catalog = Catalog::new(metas);
+    let mut summary = Summary::default();
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        summary.total += 1;
+        writeln!(out, "{}", line)?;
+        let (message, whole) = match extract_message(&line, &arg.format, &arg.message_field) {
+            Some(message) => (message, true),
+            None => (line.clone(), false),
+        };
+        match catalog.lookup(&message, whole) {
+            Some(meta) => {
+                summary.matched += 1;
+                write_meta(out, meta)?;
+            }
+            None => {
+                *summary
+                    .unmatched
+                    .entry(message.trim().to_owned())
+                    .or_default() += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+fn write_meta<W: Write>(out: &mut W, meta: &Meta) -> Result<(), Box<dyn Error>> {
+    writeln!(out, "    [{}] {}", meta.level, meta.subject.format())?;
+    for line in meta.description.0.lines() {
+        writeln!(out, "    {}", line)?;
+    }
+    writeln!(out, "    at {}:{}", meta.file, meta.line)?;
+    Ok(())
+}
+
+pub fn write_summary<W: Write>(out: &mut W, summary: &Summary) -> Result<(), Box<dyn Error>> {
+    let unmatched: usize = summary.unmatched.values().sum();
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{} lines, {} explained, {} unmatched",
+        summary.total, summary.matched, unmatched
+    )?;
+    if !summary.unmatched.is_empty() {
+        writeln!(out, "undocumented messages:")?;
+        let mut messages: Vec<(&String, &usize)> = summary.unmatched.iter().collect();
+        messages.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (message, count) in messages {
+            writeln!(out, "{:>6} {}", count, message)?;
+        }
+    }
+    Ok(())
+}
+
+/// Message of a structured log line, `None` for plain lines or when the
+/// field is absent.
+pub fn extract_message(line: &str, format: &InputFormat, field: &str) -> Option<String> {
+    match format {
+        InputFormat::Plain => None,
+        InputFormat::Logfmt => parse_logfmt(line)
+            .into_iter()
+            .find(|(k, _)| k == field)
+            .map(|(_, v)| v),
+        InputFormat::Json => {
+            let value: serde_json::Value = serde_json::from_str(line).ok()?;
+            match value.get(field)? {
+                serde_json::Value::String(s) => Some(s.clone()),
+                other => Some(other.to_string()),
+            }
+        }
+    }
+}
+
+pub fn parse_logfmt(line: &str) -> Vec<(String, String)> {
+    let mut pairs = vec![];
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+        let mut key = String::new();
+        while let Some(c) = chars.peek() {
+            if c.is_whitespace() || *c == '=' {
+                break;
+            }
+            key.push(*c);
+            chars.next();
+        }
+        let mut value = String::new();
+        if chars.peek() == Some(&'=') {
+            chars.next();
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(c) => value.push(c),
+                            None => break,
+                        },
+                        c => value.push(c),
+                    }
+                }
+            } else {
+                while let Some(c) = chars.peek() {
+                    if c.is_whitespace() {
+                        break;
+                    }
+                    value.push(*c);
+                    chars.next();
+                }
+            }
+        }
+        pairs.push((key, value));
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::meta::{Description, Level, Message, Subject};
+
+    use super::*;
+
+    fn metas() -> Vec<Meta> {
+        vec![
+            Meta {
+                level: Level::Warn,
+                message: Message("connection to %s lost".to_owned()),
+                subject: Subject("database is unreachable\n".to_owned()),
+                description: Description("check the network\n".to_owned()),
+                file: "main.go".to_owned(),
+                line: 12,
+                ..Default::default()
+            },
+            Meta {
+                level: Level::Info,
+                message: Message("connection to {} lost, retrying".to_owned()),
+                subject: Subject("reconnect".to_owned()),
+                file: "main.go".to_owned(),
+                line: 20,
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn explain_arg(format: InputFormat) -> ExplainArg {
+        ExplainArg {
+            input: None,
+            format,
+            message_field: "msg".to_owned(),
+        }
+    }
+
+    #[test]
+    fn logfmt() {
+        let pairs = parse_logfmt(r#"ts=1 level=warn msg="connection \"db\" lost" flag"#);
+        assert_eq!(
+            pairs,
+            vec![
+                ("ts".to_owned(), "1".to_owned()),
+                ("level".to_owned(), "warn".to_owned()),
+                ("msg".to_owned(), "connection \"db\" lost".to_owned()),
+                ("flag".to_owned(), "".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn explain_plain() {
+        let metas = metas();
+        let input = "2024-01-01 WARN connection to db lost\n2024-01-01 INFO connection to db lost, retrying\nsomething else\n";
+        let mut out = vec![];
+        let summary = explain(
+            &metas,
+            input.as_bytes(),
+            &mut out,
+            &explain_arg(InputFormat::Plain),
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.matched, 2);
+        assert_eq!(summary.unmatched.get("something else"), Some(&1));
+        assert!(out.contains(
+            "    [warn] database is unreachable\n    check the network\n    at main.go:12\n"
+        ));
+        assert!(out.contains("    [info] reconnect\n    at main.go:20\n"));
+    }
+
+    #[test]
+    fn explain_json() {
+        let metas = metas();
+        let input =
+            "{\"msg\":\"connection to db lost\",\"level\":\"warn\"}\n{\"msg\":\"timeout\"}\n";
+        let mut out = vec![];
+        let summary = explain(
+            &metas,
+            input.as_bytes(),
+            &mut out,
+            &explain_arg(InputFormat::Json),
+        )
+        .unwrap();
+        assert_eq!(summary.matched, 1);
+        assert_eq!(summary.unmatched.get("timeout"), Some(&1));
+    }
+}
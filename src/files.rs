@@ -16,27 +16,29 @@ pub fn form_list_files(arg: &args::Arg) -> Result<Vec<String>, Box<dyn std::erro
     let mut total = vec![];
     if let Some(files) = &arg.files {
         for file in files {
-            if std::fs::metadata(&file)?.is_file() {
+            if std::fs::metadata(file)?.is_file() {
                 total.push(file.clone());
             }
         }
     }
     let recurse = arg.recurse;
-    let mut files = list_files_in_dir(&arg.directories(), recurse)?;
+    let lang = crate::language::Language::from(&arg.language()?);
+    let mut files = list_files_in_dir(&arg.directories(), recurse, lang.extensions())?;
     total.append(&mut files);
 
     Ok(total)
 }
-fn list_files_in_dir<T>(
+pub(crate) fn list_files_in_dir<T>(
     dirs: &Vec<T>,
     recurse: bool,
+    extensions: &[&str],
 ) -> Result<Vec<String>, Box<dyn std::error::Error>>
 where
     T: AsRef<Path>,
 {
     let mut files_total = vec![];
     for dir in dirs {
-        let mut files = walk_path(dir.as_ref(), recurse)?;
+        let mut files = walk_path(dir.as_ref(), recurse, extensions)?;
         files_total.append(&mut files);
     }
     Ok(files_total)
@@ -45,6 +47,7 @@ where
 fn walk_path(
     path: &std::path::Path,
     recurse: bool,
+    extensions: &[&str],
 ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
     let mut files = vec![];
     let entries = std::fs::read_dir(path)?;
@@ -57,12 +60,21 @@ fn walk_path(
             continue;
         }
         if entry.file_type()?.is_dir() {
-            if recurse {
-                let mut files_in_entry = walk_path(path.as_ref(), recurse)?;
+            if recurse && !is_skipped(&entry) {
+                let mut files_in_entry = walk_path(&entry.path(), recurse, extensions)?;
                 files.append(&mut files_in_entry);
             }
             continue;
         }
+        let matches = entry
+            .path()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| extensions.contains(&ext))
+            .unwrap_or(false);
+        if !matches {
+            continue;
+        }
         if let Ok(path) = entry.path().into_os_string().into_string() {
             files.push(path);
         }
@@ -78,6 +90,17 @@ fn is_hidden(entry: &std::fs::DirEntry) -> bool {
         .unwrap_or(false)
 }
 
+/// Dependency and build directories that never hold project sources.
+pub(crate) const SKIPPED_DIRS: [&str; 4] = ["node_modules", "target", "vendor", "__pycache__"];
+
+fn is_skipped(entry: &std::fs::DirEntry) -> bool {
+    entry
+        .file_name()
+        .to_str()
+        .map(|s| SKIPPED_DIRS.contains(&s))
+        .unwrap_or(false)
+}
+
 pub fn search_in_file_dyn<T, W>(data: T, pos: &W) -> Vec<u8>
 where
     T: AsRef<[u8]>,
@@ -86,7 +109,7 @@ where
     let mut lines: Vec<Vec<u8>> = vec![];
     let mut local_line: Vec<u8> = vec![];
     for byte in data.as_ref() {
-        local_line.push(byte.clone());
+        local_line.push(*byte);
         if byte.eq(&10) {
             lines.push(local_line.clone());
             local_line.clear();
@@ -109,45 +132,64 @@ where
 pub fn write_to_file(meta: Meta, arg: &args::Arg) -> Result<(), Box<dyn std::error::Error>> {
     let save_path = form_file_name(&arg.save_path, arg, &meta.level);
 
-    let mut file = create_new(&save_path, &arg, &meta)?;
+    let file = create_new(&save_path, arg, &meta)?;
     if arg.save_type == SaveType::MD {
-        write_description(&mut file, &arg, &meta)?;
-        write_markdown_table_header(&mut file, &arg)?;
-        write_markdown_data(&mut file, &meta)?;
+        write_description(&file, arg, &meta)?;
+        write_markdown_table_header(&file, arg)?;
+        write_markdown_data(&file, &meta)?;
     }
 
     Ok(())
 }
 
-fn form_file_name(dir: &String, arg: &args::Arg, level: &Level) -> String {
+pub fn form_file_name(dir: &String, arg: &args::Arg, level: &Level) -> String {
     let path = std::path::Path::new(dir);
-    return match level {
-        Level::Info => format!(
-            "{}.{}",
-            path.join("info").display().to_string(),
-            arg.file_suffix()
-        ),
-        Level::Debug => format!(
-            "{}.{}",
-            path.join("debug").display().to_string(),
-            arg.file_suffix()
-        ),
-        Level::Trace => format!(
-            "{}.{}",
-            path.join("trace").display().to_string(),
-            arg.file_suffix()
-        ),
-        Level::Warn => format!(
-            "{}.{}",
-            path.join("warn").display().to_string(),
-            arg.file_suffix()
-        ),
-        Level::Fatal => format!(
-            "{}.{}",
-            path.join("fatal").display().to_string(),
-            arg.file_suffix()
-        ),
-    };
+    match level {
+        Level::Info => format!("{}.{}", path.join("info").display(), arg.file_suffix()),
+        Level::Debug => format!("{}.{}", path.join("debug").display(), arg.file_suffix()),
+        Level::Trace => format!("{}.{}", path.join("trace").display(), arg.file_suffix()),
+        Level::Warn => format!("{}.{}", path.join("warn").display(), arg.file_suffix()),
+        Level::Fatal => format!("{}.{}", path.join("fatal").display(), arg.file_suffix()),
+    }
+}
+
+/// Directory for the documents of one group: `billing/api` and
+/// `crate::billing` become `billing_api` and `crate_billing`.
+fn group_dir_name(group: &str) -> String {
+    let mut name = String::with_capacity(group.len());
+    for c in group.chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            name.push(c);
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    let name = name.trim_end_matches('_');
+    if name.is_empty() {
+        "root".to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Directories for the documents of `groups`, one per group and in the same
+/// order. Groups that sanitize to the same name (`a/b` and `a_b`) get a
+/// numeric suffix, so no two groups write into one directory.
+pub fn group_dir_names(groups: &[String]) -> Vec<String> {
+    let mut used = std::collections::HashSet::new();
+    groups
+        .iter()
+        .map(|group| {
+            let base = group_dir_name(group);
+            let mut name = base.clone();
+            let mut n = 2;
+            while !used.insert(name.clone()) {
+                name = format!("{base}_{n}");
+                n += 1;
+            }
+            name
+        })
+        .collect()
 }
 
 pub fn save_string_to_file<T>(data: T, level: &Level, arg: &args::Arg) -> Result<(), Box<dyn Error>>
@@ -155,27 +197,188 @@ where
     T: AsRef<[u8]>,
 {
     let save_path = form_file_name(&arg.save_path, arg, level);
+    save(data, &save_path, arg.dry_run)?;
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WriteStatus {
+    Created,
+    Updated,
+    Unchanged,
+}
+
+/// Write `data` to `save_path` unless the file already has this content.
+/// With `dry_run` nothing is written, the planned change is printed instead.
+pub fn save<T, P>(data: T, save_path: P, dry_run: bool) -> Result<WriteStatus, Box<dyn Error>>
+where
+    T: AsRef<[u8]>,
+    P: AsRef<Path>,
+{
+    let save_path = save_path.as_ref();
+    if std::fs::metadata(save_path).is_ok_and(|m| !m.is_file()) {
+        // devices and pipes (`-o /dev/stdout`) can't be compared or replaced
+        if !dry_run {
+            std::fs::OpenOptions::new()
+                .write(true)
+                .open(save_path)?
+                .write_all(data.as_ref())?;
+        }
+        return Ok(WriteStatus::Updated);
+    }
+    let current = std::fs::read(save_path).ok();
+    let status = match &current {
+        None => WriteStatus::Created,
+        Some(current) if current == data.as_ref() => WriteStatus::Unchanged,
+        Some(_) => WriteStatus::Updated,
+    };
+    if dry_run {
+        match status {
+            WriteStatus::Created => println!("would create {}", save_path.display()),
+            WriteStatus::Updated => {
+                let (added, removed) =
+                    changed_lines(current.as_deref().unwrap_or_default(), data.as_ref());
+                println!(
+                    "would update {} (+{} -{} lines)",
+                    save_path.display(),
+                    added,
+                    removed
+                );
+            }
+            WriteStatus::Unchanged => println!("unchanged {}", save_path.display()),
+        }
+        return Ok(status);
+    }
+    if status != WriteStatus::Unchanged {
+        save_string_to_path(data, save_path)?;
+    }
+    Ok(status)
+}
+
+/// Atomically replace `save_path` with `data`: the content is written to a
+/// temporary file in the same directory which is then renamed over the
+/// target. A symlink is kept and its target replaced, the permissions of
+/// the replaced file are kept. Missing parent directories are created.
+pub fn save_string_to_path<T, P>(data: T, save_path: P) -> Result<(), Box<dyn Error>>
+where
+    T: AsRef<[u8]>,
+    P: AsRef<Path>,
+{
+    let save_path = &resolve_symlinks(save_path.as_ref())?;
+    let dir = match save_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(dir)?;
+    let file_name = save_path
+        .file_name()
+        .ok_or(format!("{} is not a file path", save_path.display()))?;
+    let tmp_path = dir.join(format!(
+        ".{}.logdoc-{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    let result = write_and_rename(data.as_ref(), &tmp_path, save_path);
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Path of the file `path` points to through symlinks; the target does
+/// not need to exist.
+fn resolve_symlinks(path: &Path) -> Result<std::path::PathBuf, Box<dyn Error>> {
+    let mut path = path.to_path_buf();
+    for _ in 0..40 {
+        if !std::fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_symlink()) {
+            return Ok(path);
+        }
+        let link = std::fs::read_link(&path)?;
+        path = match path.parent() {
+            Some(dir) => dir.join(link),
+            None => link,
+        };
+    }
+    Err(format!("{}: too many levels of symbolic links", path.display()).into())
+}
+
+fn write_and_rename(data: &[u8], tmp_path: &Path, save_path: &Path) -> Result<(), Box<dyn Error>> {
     let mut file = std::fs::OpenOptions::new()
         .create(true)
         .write(true)
-        .open(&save_path)?;
-    file.write(data.as_ref())?;
+        .truncate(true)
+        .open(tmp_path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    if let Ok(metadata) = std::fs::metadata(save_path) {
+        std::fs::set_permissions(tmp_path, metadata.permissions())?;
+    }
+    std::fs::rename(tmp_path, save_path)?;
     Ok(())
 }
 
+/// Number of added and removed lines between two versions of a file.
+fn changed_lines(old: &[u8], new: &[u8]) -> (usize, usize) {
+    let old = String::from_utf8_lossy(old);
+    let new = String::from_utf8_lossy(new);
+    let mut counts: std::collections::HashMap<&str, isize> = std::collections::HashMap::new();
+    for line in old.lines() {
+        *counts.entry(line).or_default() -= 1;
+    }
+    for line in new.lines() {
+        *counts.entry(line).or_default() += 1;
+    }
+    let added = counts.values().filter(|c| **c > 0).sum::<isize>() as usize;
+    let removed = counts
+        .values()
+        .filter(|c| **c < 0)
+        .map(|c| -c)
+        .sum::<isize>() as usize;
+    (added, removed)
+}
+
+/// Compare expected content with files on disk, error lists stale files.
+pub fn check_files<P, T>(outputs: &[(P, T)]) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+    T: AsRef<[u8]>,
+{
+    let stale = stale_files(outputs);
+    if stale.is_empty() {
+        return Ok(());
+    }
+    Err(format!("out of date: {}", stale.join(", ")).into())
+}
+
+/// Paths of `outputs` whose files are missing or differ from the content.
+pub fn stale_files<P, T>(outputs: &[(P, T)]) -> Vec<String>
+where
+    P: AsRef<Path>,
+    T: AsRef<[u8]>,
+{
+    outputs
+        .iter()
+        .filter(|(path, data)| match std::fs::read(path) {
+            Ok(current) => current != data.as_ref(),
+            Err(_) => true,
+        })
+        .map(|(path, _)| path.as_ref().display().to_string())
+        .collect()
+}
+
 fn create_new(
     path: &String,
     arg: &args::Arg,
     meta: &Meta,
 ) -> Result<std::fs::File, Box<dyn std::error::Error>> {
-    let project = arg.project_name.clone();
+    let project = arg.project_name();
     let mut file = std::fs::File::create(path)?;
-    file.write(format!("# {} - {} logs\n\n", project, meta.level).as_bytes())?;
+    file.write_all(format!("# {} - {} logs\n\n", project, meta.level).as_bytes())?;
     Ok(file)
 }
 
 fn write_description(
-    mut file: &File,
+    file: &File,
     arg: &args::Arg,
     meta: &Meta,
 ) -> Result<(), Box<dyn std::error::Error>> {
@@ -183,35 +386,35 @@ fn write_description(
         Level::Info => {
             if let Some(ref desc) = arg.info_desc {
                 if desc.len() > 1 {
-                    write_file_to_end(&mut file, desc)?;
+                    write_file_to_end(file, desc)?;
                 }
             }
         }
         Level::Debug => {
             if let Some(ref desc) = arg.debug_desc {
                 if desc.len() > 1 {
-                    write_file_to_end(&mut file, desc)?;
+                    write_file_to_end(file, desc)?;
                 }
             }
         }
         Level::Trace => {
             if let Some(ref desc) = arg.trace_desc {
                 if desc.len() > 1 {
-                    write_file_to_end(&mut file, desc)?;
+                    write_file_to_end(file, desc)?;
                 }
             }
         }
         Level::Warn => {
             if let Some(ref desc) = arg.warn_desc {
                 if desc.len() > 1 {
-                    write_file_to_end(&mut file, desc)?;
+                    write_file_to_end(file, desc)?;
                 }
             }
         }
         Level::Fatal => {
             if let Some(ref desc) = arg.fatal_desc {
                 if desc.len() > 1 {
-                    write_file_to_end(&mut file, desc)?;
+                    write_file_to_end(file, desc)?;
                 }
             }
         }
@@ -225,7 +428,7 @@ fn write_file_to_end(
     data: &String,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let data = format!("{data}\n\n");
-    file.write(data.as_bytes())?;
+    file.write_all(data.as_bytes())?;
     Ok(())
 }
 
@@ -249,7 +452,7 @@ fn write_markdown_table_header(
         "|{}|{}|{}|\n|---|---|---|\n",
         msg_header, subject_header, description_header
     );
-    file.write(data.as_bytes())?;
+    file.write_all(data.as_bytes())?;
     Ok(())
 }
 
@@ -258,13 +461,77 @@ fn write_markdown_data(mut file: &File, meta: &Meta) -> Result<(), Box<dyn std::
     let subject = meta.subject.format();
     let description = meta.description.format();
     let data = format!("|{}|{}|{}|\n", message, subject, description);
-    file.write(data.as_bytes())?;
+    file.write_all(data.as_bytes())?;
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{files::search_in_file_dyn, meta::Pos};
+    use crate::{
+        files::{group_dir_names, save, search_in_file_dyn, WriteStatus},
+        meta::Pos,
+    };
+
+    #[test]
+    fn test_group_dir_names() {
+        let groups: Vec<String> = ["a/b", "a_b", "a_b_2", "crate::billing", ""]
+            .iter()
+            .map(|g| g.to_string())
+            .collect();
+        assert_eq!(
+            group_dir_names(&groups),
+            vec!["a_b", "a_b_2", "a_b_2_2", "crate_billing", "root"]
+        );
+    }
+
+    #[test]
+    fn test_save() {
+        let dir = std::env::temp_dir().join(format!("logdoc-save-{}", std::process::id()));
+        let path = dir.join("nested").join("info.md");
+        assert_eq!(
+            save("long content\n", &path, true).unwrap(),
+            WriteStatus::Created
+        );
+        assert!(!path.exists());
+        assert_eq!(
+            save("long content\n", &path, false).unwrap(),
+            WriteStatus::Created
+        );
+        assert_eq!(
+            save("long content\n", &path, false).unwrap(),
+            WriteStatus::Unchanged
+        );
+        assert_eq!(save("short\n", &path, false).unwrap(), WriteStatus::Updated);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "short\n");
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_through_symlink() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("logdoc-symlink-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("docs")).unwrap();
+        let target = dir.join("docs").join("warn.md");
+        std::fs::write(&target, "old\n").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o640)).unwrap();
+        let link = dir.join("warn.md");
+        std::os::unix::fs::symlink("docs/warn.md", &link).unwrap();
+        assert_eq!(save("new\n", &link, false).unwrap(), WriteStatus::Updated);
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new\n");
+        let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 
     #[test]
     fn test_walk_file_dyn() {
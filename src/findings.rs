@@ -0,0 +1,453 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::Display,
+};
+
+use serde_json::json;
+
+use crate::{
+    app::{Application, Scan},
+    args::{self, CheckArg, FindingsFormat},
+    baseline::Baseline,
+    duplicates, files,
+    language::{Comment, Language},
+};
+
+/// Problem found in the sources, identified across runs by file, rule and
+/// normalized message so moved lines still match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.file, self.line, self.rule, self.message
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub help: &'static str,
+    /// SARIF level: `warning` or `error`
+    pub severity: &'static str,
+}
+
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: "malformed-annotation",
+        description: "Annotation has no message",
+        help: "Write the message after the level: `// Warn: message text`.",
+        severity: "error",
+    },
+    Rule {
+        id: "unknown-level",
+        description: "Annotation of a log call uses an unknown level",
+        help: "Use info, debug, trace, warn (warning) or fatal (error, critical, exception).",
+        severity: "error",
+    },
+    Rule {
+        id: "undocumented",
+        description: "Log call has no annotation",
+        help: "Add a `// Level: message` comment with subject and description above the call.",
+        severity: "warning",
+    },
+    Rule {
+        id: "duplicate",
+        description: "Message is documented more than once",
+        help: "Remove the copies or merge them with `--duplicates merge`.",
+        severity: "warning",
+    },
+    Rule {
+        id: "conflicting-duplicate",
+        description: "Message is documented with different subjects or descriptions",
+        help: "Keep one annotation or make the messages distinct.",
+        severity: "warning",
+    },
+    Rule {
+        id: "near-duplicate",
+        description: "Messages differ only in case, whitespace or placeholders",
+        help: "Use the same text for the same event or make the messages distinct.",
+        severity: "warning",
+    },
+    Rule {
+        id: "level-conflict",
+        description: "Message is documented at different levels",
+        help: "Log the message at one level or make the messages distinct.",
+        severity: "warning",
+    },
+    Rule {
+        id: "stale-docs",
+        description: "Generated document is out of date",
+        help: "Regenerate the documents with logdoc and commit them.",
+        severity: "error",
+    },
+];
+
+pub fn rule(id: &str) -> &'static Rule {
+    RULES
+        .iter()
+        .find(|rule| rule.id == id)
+        .expect("findings use known rules")
+}
+
+/// Level names of other logging libraries (syslog, java.util.logging, ...)
+/// that logdoc doesn't know; other words before a colon start ordinary
+/// comments.
+const UNKNOWN_LEVELS: &[&str] = &[
+    "notice",
+    "err",
+    "crit",
+    "alert",
+    "emerg",
+    "emergency",
+    "panic",
+    "severe",
+    "fine",
+    "finer",
+    "finest",
+    "config",
+    "verbose",
+    "dbg",
+    "inf",
+    "wrn",
+    "information",
+    "informational",
+];
+
+/// Malformed annotations, unknown levels, undocumented log calls,
+/// duplicated messages and, with `--check`, stale documents.
+pub fn collect(scan: &Scan, arg: &args::Arg) -> Result<Vec<Finding>, Box<dyn Error>> {
+    let lang = Language::from(&arg.language()?);
+    let metas = &scan.metas;
+    let mut findings = scan.problems.clone();
+
+    let documented: HashSet<(&str, usize)> = metas
+        .iter()
+        .filter_map(|m| m.call.as_ref().map(|call| (m.file.as_str(), call.line)))
+        .collect();
+    let mut sources: HashMap<String, Vec<String>> = HashMap::new();
+    for (file, call) in &scan.calls {
+        if documented.contains(&(file.as_str(), call.line)) {
+            continue;
+        }
+        if !sources.contains_key(file) {
+            let source = std::fs::read_to_string(file)?;
+            sources.insert(file.clone(), source.lines().map(String::from).collect());
+        }
+        let keyword = comment_keyword(&sources[file], call.line, &lang.comment());
+        if let Some(keyword) = keyword {
+            findings.push(Finding {
+                rule: "unknown-level",
+                file: file.clone(),
+                line: call.line,
+                message: format!("unknown level \"{keyword}\" for {}", call.function),
+            });
+            continue;
+        }
+        // calls without a level (`print`, ...) need no annotation
+        let Some(level) = call.level() else {
+            continue;
+        };
+        let message = match &call.message {
+            Some(message) => format!("{level} call {} \"{message}\"", call.function),
+            None => format!("{level} call {}", call.function),
+        };
+        findings.push(Finding {
+            rule: "undocumented",
+            file: file.clone(),
+            line: call.line,
+            message,
+        });
+    }
+
+    for duplicate in duplicates::find(metas) {
+        let first = &duplicate.locations[0];
+        findings.push(Finding {
+            rule: duplicate.kind.rule(),
+            file: first.file.clone(),
+            line: first.line,
+            message: format!("{} message \"{}\"", duplicate.kind, duplicate.message),
+        });
+    }
+
+    if arg.check && arg.inject.is_none() {
+        for path in files::stale_files(&Application::outputs(arg, metas)?) {
+            findings.push(Finding {
+                rule: "stale-docs",
+                file: path,
+                line: 1,
+                message: "document is out of date".to_owned(),
+            });
+        }
+    }
+    Ok(findings)
+}
+
+/// Keyword of a `Keyword: text` comment block right above line `line`
+/// (1-based) when it is a level logdoc doesn't know, e.g. `notice` of
+/// `// Notice: ...`.
+fn comment_keyword(lines: &[String], line: usize, comment: &Comment) -> Option<String> {
+    let mut first = None;
+    for text in lines[..line.saturating_sub(1)].iter().rev() {
+        let text = text.trim();
+        match comment
+            .line
+            .iter()
+            .find(|prefix| text.starts_with(**prefix))
+        {
+            Some(prefix) => first = Some(text[prefix.len()..].trim().to_owned()),
+            None => break,
+        }
+    }
+    let first = first?;
+    let (keyword, rest) = first.split_once(':')?;
+    let keyword = keyword.trim();
+    if rest.trim().is_empty() || !UNKNOWN_LEVELS.contains(&keyword.to_lowercase().as_str()) {
+        return None;
+    }
+    Some(keyword.to_owned())
+}
+
+pub fn run(scan: &Scan, arg: &args::Arg, check: &CheckArg) -> Result<(), Box<dyn Error>> {
+    let findings = collect(scan, arg)?;
+    let root = std::path::Path::new(&check.baseline)
+        .parent()
+        .unwrap_or(std::path::Path::new(""));
+    if check.write_baseline {
+        let baseline = Baseline::new(&findings, root);
+        files::save(baseline.to_json()?, &check.baseline, arg.dry_run)?;
+        return Ok(());
+    }
+    let baseline = Baseline::load(&check.baseline)?;
+    let (new, resolved) = baseline.compare(&findings, root);
+    let data = render(&new, &check.format)?;
+    match &check.output {
+        Some(output) => {
+            files::save(data, output, arg.dry_run)?;
+        }
+        None => print!("{data}"),
+    }
+    for entry in &resolved {
+        eprintln!(
+            "resolved: {}: {}: {} (prune with --write-baseline)",
+            entry.file, entry.rule, entry.message
+        );
+    }
+    eprintln!(
+        "{} new, {} in baseline, {} resolved",
+        new.len(),
+        findings.len() - new.len(),
+        resolved.len()
+    );
+    if new.is_empty() {
+        return Ok(());
+    }
+    Err(format!("{} new findings", new.len()).into())
+}
+
+pub fn render(findings: &[&Finding], format: &FindingsFormat) -> Result<String, Box<dyn Error>> {
+    let mut out = String::new();
+    match format {
+        FindingsFormat::Text => {
+            for finding in findings {
+                out.push_str(&format!("{finding}\n"));
+            }
+        }
+        FindingsFormat::JsonLines => {
+            for finding in findings {
+                let rule = rule(finding.rule);
+                let line = json!({
+                    "rule": finding.rule,
+                    "severity": rule.severity,
+                    "file": finding.file,
+                    "line": finding.line,
+                    "message": finding.message,
+                    "help": rule.help,
+                });
+                out.push_str(&format!("{line}\n"));
+            }
+        }
+        FindingsFormat::Github => {
+            for finding in findings {
+                let command = match rule(finding.rule).severity {
+                    "error" => "error",
+                    _ => "warning",
+                };
+                out.push_str(&format!(
+                    "::{command} file={},line={},title={}::{}\n",
+                    escape_property(&finding.file),
+                    finding.line,
+                    finding.rule,
+                    escape_data(&finding.message)
+                ));
+            }
+        }
+        FindingsFormat::Sarif => out = serde_json::to_string_pretty(&sarif(findings))? + "\n",
+    }
+    Ok(out)
+}
+
+fn sarif(findings: &[&Finding]) -> serde_json::Value {
+    let rules: Vec<serde_json::Value> = RULES
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": { "text": rule.description },
+                "help": { "text": rule.help },
+                "defaultConfiguration": { "level": rule.severity },
+            })
+        })
+        .collect();
+    let results: Vec<serde_json::Value> = findings
+        .iter()
+        .map(|finding| {
+            json!({
+                "ruleId": finding.rule,
+                "ruleIndex": RULES.iter().position(|rule| rule.id == finding.rule),
+                "level": rule(finding.rule).severity,
+                "message": { "text": finding.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": finding.file.trim_start_matches("./").replace('\\', "/"),
+                        },
+                        "region": { "startLine": finding.line },
+                    }
+                }],
+            })
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "logdoc",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// Workflow command message escaping.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Workflow command property escaping.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{args::FindingsFormat, language::Comment};
+
+    use clap::Parser;
+
+    use super::{collect, comment_keyword, render, rule, Finding, RULES};
+    use crate::{app::Application, args::Arg};
+
+    #[test]
+    fn undocumented() {
+        let arg = Arg::try_parse_from([
+            "logdoc",
+            "-p",
+            "project",
+            "-l",
+            "golang",
+            "-f",
+            "tests/go/main.go",
+            "check",
+        ])
+        .unwrap();
+        let scan = Application::collect(&arg).unwrap();
+        let findings = collect(&scan, &arg).unwrap();
+        let lines: Vec<(&str, usize)> = findings.iter().map(|f| (f.rule, f.line)).collect();
+        assert_eq!(
+            lines,
+            [
+                ("undocumented", 16),
+                ("undocumented", 18),
+                ("undocumented", 22)
+            ]
+        );
+        // `return fmt.Errorf(...)` wraps an error, it doesn't log
+        assert!(findings.iter().all(|f| !f.message.contains("fmt.Errorf")));
+    }
+
+    #[test]
+    fn unknown_level() {
+        let lines: Vec<String> = [
+            "func main() {",
+            "\t// Notice: cache is cold",
+            "\t// caches are rebuilt",
+            "\tlog.Print(\"cold cache\")",
+            "\t// TODO: remove",
+            "\tlog.Info(\"x\")",
+            "\t// Retry: the pool reconnects",
+            "\tlog.Print(\"reconnect\")",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        assert_eq!(
+            comment_keyword(&lines, 4, &Comment::SLASH).as_deref(),
+            Some("Notice")
+        );
+        assert_eq!(comment_keyword(&lines, 6, &Comment::SLASH), None);
+        assert_eq!(comment_keyword(&lines, 8, &Comment::SLASH), None);
+        assert_eq!(comment_keyword(&lines, 1, &Comment::SLASH), None);
+    }
+
+    #[test]
+    fn formats() {
+        for r in RULES {
+            assert_eq!(rule(r.id), r);
+        }
+        let finding = Finding {
+            rule: "undocumented",
+            file: "./src/main.go".to_owned(),
+            line: 12,
+            message: "warn call log.Warn \"100%\"".to_owned(),
+        };
+        let findings = [&finding];
+        assert_eq!(
+            render(&findings, &FindingsFormat::Github).unwrap(),
+            "::warning file=./src/main.go,line=12,title=undocumented::warn call log.Warn \"100%25\"\n"
+        );
+        let line: serde_json::Value =
+            serde_json::from_str(&render(&findings, &FindingsFormat::JsonLines).unwrap()).unwrap();
+        assert_eq!(line["severity"], "warning");
+        let sarif: serde_json::Value =
+            serde_json::from_str(&render(&findings, &FindingsFormat::Sarif).unwrap()).unwrap();
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "undocumented");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "src/main.go"
+        );
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startLine"],
+            12
+        );
+    }
+}
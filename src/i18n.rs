@@ -0,0 +1,336 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    args::{Arg, Column},
+    meta::{Description, Meta, Subject},
+};
+
+/// Subject and description of an entry in another language.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Translation {
+    pub subject: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Table headers in another language.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Headers {
+    pub message: Option<String>,
+    pub subject: Option<String>,
+    pub description: Option<String>,
+}
+
+impl Headers {
+    /// Translated title of `column`, if any.
+    pub fn title(&self, column: Column) -> Option<&String> {
+        match column {
+            Column::Message => self.message.as_ref(),
+            Column::Subject => self.subject.as_ref(),
+            Column::Description => self.description.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Translations of one locale: JSON files keyed by message ID
+/// (`Meta::id`) or gettext `.po` files keyed by source text.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Catalog {
+    pub locale: String,
+    #[serde(default)]
+    pub headers: Headers,
+    #[serde(default)]
+    pub messages: BTreeMap<String, Translation>,
+    /// `msgid` to `msgstr` of `.po` files
+    #[serde(skip)]
+    pub texts: HashMap<String, String>,
+}
+
+/// Entry without a translation of its subject or description.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Untranslated {
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl Catalog {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Catalog, Box<dyn Error>> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)?;
+        let catalog = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("po") => {
+                let stem = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or_default();
+                Catalog::from_po(&data, stem)
+            }
+            _ => serde_json::from_str(&data).map_err(|e| e.to_string()),
+        };
+        catalog.map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    /// Catalog of a gettext file; the locale comes from the `Language`
+    /// header, `locale` when there is none. `msgid`s `message`, `subject`
+    /// and `description` translate the table headers.
+    pub fn from_po(data: &str, locale: &str) -> Result<Catalog, String> {
+        let mut catalog = Catalog {
+            locale: locale.to_owned(),
+            ..Default::default()
+        };
+        for (msgid, msgstr) in parse_po(data)? {
+            if msgid.is_empty() {
+                let language = msgstr
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Language:"))
+                    .map(|language| language.trim());
+                if let Some(language) = language.filter(|l| !l.is_empty()) {
+                    catalog.locale = language.to_owned();
+                }
+                continue;
+            }
+            if !msgstr.is_empty() {
+                catalog.texts.insert(msgid, msgstr);
+            }
+        }
+        let header = |name: &str| catalog.texts.get(name).cloned();
+        catalog.headers = Headers {
+            message: header("message"),
+            subject: header("subject"),
+            description: header("description"),
+        };
+        Ok(catalog)
+    }
+
+    /// Add translations of `other`, keeping the ones already present.
+    pub fn merge(&mut self, other: Catalog) {
+        let headers = &mut self.headers;
+        headers.message = headers.message.take().or(other.headers.message);
+        headers.subject = headers.subject.take().or(other.headers.subject);
+        headers.description = headers.description.take().or(other.headers.description);
+        for (id, translation) in other.messages {
+            self.messages.entry(id).or_insert(translation);
+        }
+        for (msgid, msgstr) in other.texts {
+            self.texts.entry(msgid).or_insert(msgstr);
+        }
+    }
+
+    /// Translation of `meta`: `subject[locale]:` annotations first, then
+    /// the message ID, then the source text.
+    pub fn translate(&self, meta: &Meta) -> Translation {
+        let annotated = meta.translations.get(&self.locale);
+        let by_id = self.messages.get(&meta.id());
+        let by_text = |text: String| self.texts.get(&text).cloned();
+        Translation {
+            subject: annotated
+                .and_then(|t| t.subject.clone())
+                .or(by_id.and_then(|t| t.subject.clone()))
+                .or_else(|| by_text(meta.subject.format())),
+            description: annotated
+                .and_then(|t| t.description.clone())
+                .or(by_id.and_then(|t| t.description.clone()))
+                .or_else(|| by_text(meta.description.0.trim_end().to_owned())),
+        }
+    }
+
+    /// Entries in this locale, untranslated parts left in the source
+    /// language, and the entries missing a translation.
+    pub fn localize(&self, metas: &[Meta]) -> (Vec<Meta>, Vec<Untranslated>) {
+        let mut localized = vec![];
+        let mut untranslated = vec![];
+        for meta in metas {
+            let translation = self.translate(meta);
+            let mut meta = meta.clone();
+            let missing_subject = translation.subject.is_none() && !meta.subject.0.is_empty();
+            let missing_description =
+                translation.description.is_none() && !meta.description.0.trim().is_empty();
+            if missing_subject || missing_description {
+                untranslated.push(Untranslated {
+                    file: meta.file.clone(),
+                    line: meta.line,
+                    message: meta.message.format(),
+                });
+            }
+            if let Some(subject) = translation.subject {
+                meta.subject = Subject(subject);
+            }
+            if let Some(description) = translation.description {
+                meta.description = Description(format!("{}\n", description.trim_end()));
+            }
+            localized.push(meta);
+        }
+        (localized, untranslated)
+    }
+}
+
+/// Catalogs of `--translations` files, merged by locale, plus empty ones
+/// for `--locales` and locales of `subject[locale]:` annotations.
+pub fn catalogs(arg: &Arg, metas: &[Meta]) -> Result<Vec<Catalog>, Box<dyn Error>> {
+    let mut catalogs: Vec<Catalog> = vec![];
+    let mut add = |catalog: Catalog| match catalogs.iter_mut().find(|c| c.locale == catalog.locale)
+    {
+        Some(existing) => existing.merge(catalog),
+        None => catalogs.push(catalog),
+    };
+    for path in arg.translations.iter().flatten() {
+        add(Catalog::load(path)?);
+    }
+    let annotated = metas.iter().flat_map(|m| m.translations.keys());
+    for locale in arg.locales.iter().chain(annotated) {
+        add(Catalog {
+            locale: locale.clone(),
+            ..Default::default()
+        });
+    }
+    catalogs.sort_by(|a, b| a.locale.cmp(&b.locale));
+    Ok(catalogs)
+}
+
+/// `msgid`/`msgstr` pairs of a gettext file. Plural forms and contexts
+/// are not supported.
+fn parse_po(data: &str) -> Result<Vec<(String, String)>, String> {
+    let mut entries = vec![];
+    let mut msgid: Option<String> = None;
+    let mut msgstr: Option<String> = None;
+    for (number, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = || format!("line {}: unexpected `{line}`", number + 1);
+        if let Some(rest) = line.strip_prefix("msgid ") {
+            if let (Some(id), Some(text)) = (msgid.take(), msgstr.take()) {
+                entries.push((id, text));
+            }
+            msgid = Some(po_string(rest).ok_or_else(error)?);
+        } else if let Some(rest) = line.strip_prefix("msgstr ") {
+            msgstr = Some(po_string(rest).ok_or_else(error)?);
+        } else if line.starts_with('"') {
+            let text = po_string(line).ok_or_else(error)?;
+            match (&mut msgid, &mut msgstr) {
+                (_, Some(msgstr)) => msgstr.push_str(&text),
+                (Some(msgid), None) => msgid.push_str(&text),
+                _ => return Err(error()),
+            }
+        } else if !line.starts_with("msgctxt ") {
+            return Err(error());
+        }
+    }
+    if let (Some(id), Some(text)) = (msgid, msgstr) {
+        entries.push((id, text));
+    }
+    Ok(entries)
+}
+
+fn po_string(quoted: &str) -> Option<String> {
+    let inner = quoted.trim().strip_prefix('"')?.strip_suffix('"')?;
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => text.push('\n'),
+            't' => text.push('\t'),
+            c => text.push(c),
+        }
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::meta::{Description, Level, Message, Meta, Subject};
+
+    use super::{Catalog, Translation};
+
+    fn meta(message: &str, subject: &str, description: &str) -> Meta {
+        Meta {
+            level: Level::Warn,
+            message: Message(message.to_owned()),
+            subject: Subject(subject.to_owned()),
+            description: Description(description.to_owned()),
+            file: "main.go".to_owned(),
+            line: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn po_catalog() {
+        let po = r#"
+# German
+msgid ""
+msgstr ""
+"Language: de\n"
+
+msgid "message"
+msgstr "Meldung"
+
+msgid "disk is full"
+msgstr "Festplatte ist voll"
+
+msgid ""
+"free some space\n"
+"or add a disk"
+msgstr "Platz schaffen\noder Platte einbauen"
+"#;
+        let catalog = Catalog::from_po(po, "messages").unwrap();
+        assert_eq!(catalog.locale, "de");
+        assert_eq!(catalog.headers.message.as_deref(), Some("Meldung"));
+        let (localized, untranslated) = catalog.localize(&[
+            meta(
+                "disk full",
+                "disk is full",
+                "free some space\nor add a disk\n",
+            ),
+            meta("retry", "db", ""),
+        ]);
+        assert_eq!(localized[0].subject.0, "Festplatte ist voll");
+        assert_eq!(
+            localized[0].description.0,
+            "Platz schaffen\noder Platte einbauen\n"
+        );
+        assert_eq!(localized[1].subject.0, "db");
+        assert_eq!(untranslated.len(), 1);
+        assert_eq!(untranslated[0].message, "retry");
+    }
+
+    #[test]
+    fn json_catalog_and_annotations() {
+        let mut annotated = meta("retry", "db", "");
+        annotated.translations.insert(
+            "ja".to_owned(),
+            Translation {
+                subject: Some("データベース".to_owned()),
+                description: None,
+            },
+        );
+        let id = annotated.id();
+        let catalog: Catalog = serde_json::from_str(&format!(
+            r#"{{"locale": "ja", "messages": {{"{id}": {{"subject": "DB"}}}}}}"#
+        ))
+        .unwrap();
+        assert_eq!(
+            catalog.translate(&annotated).subject.as_deref(),
+            Some("データベース")
+        );
+        annotated.translations = BTreeMap::new();
+        assert_eq!(catalog.translate(&annotated).subject.as_deref(), Some("DB"));
+    }
+}
@@ -0,0 +1,208 @@
+use std::{error::Error, ops::Range};
+
+use crate::meta::Level;
+
+pub const START_MARKER: &str = "<!-- logdoc:start";
+pub const END_MARKER: &str = "<!-- logdoc:end -->";
+
+/// Replace the content of every `<!-- logdoc:start level=... -->` ...
+/// `<!-- logdoc:end -->` block of `document` with `content(level)`, leaving
+/// the rest of the document untouched. Markers inside fenced code blocks
+/// are examples, not blocks.
+pub fn inject<F>(document: &str, mut content: F) -> Result<String, Box<dyn Error>>
+where
+    F: FnMut(&Level) -> Result<String, Box<dyn Error>>,
+{
+    let fences = fenced_regions(document);
+    let mut out = String::with_capacity(document.len());
+    let mut pos = 0;
+    while let Some(start) = find_outside(document, pos, START_MARKER, &fences) {
+        let marker_end = document[start..]
+            .find("-->")
+            .map(|i| start + i + "-->".len())
+            .ok_or("unterminated logdoc:start marker")?;
+        let level = marker_level(&document[start + START_MARKER.len()..marker_end - "-->".len()])?;
+        let block_end = find_outside(document, marker_end, END_MARKER, &fences)
+            .ok_or(format!("logdoc block for {level} logs has no {END_MARKER}"))?;
+        if find_outside(document, marker_end, START_MARKER, &fences)
+            .is_some_and(|next| next < block_end)
+        {
+            return Err(
+                format!("logdoc block for {level} logs is not closed before the next one").into(),
+            );
+        }
+        out.push_str(&document[pos..marker_end]);
+        out.push('\n');
+        let text = content(&level)?;
+        let text = text.trim();
+        if !text.is_empty() {
+            out.push_str(text);
+            out.push('\n');
+        }
+        out.push_str(END_MARKER);
+        pos = block_end + END_MARKER.len();
+    }
+    out.push_str(&document[pos..]);
+    Ok(out)
+}
+
+/// Position of the first `needle` at or after `from` outside of `fences`.
+fn find_outside(
+    document: &str,
+    from: usize,
+    needle: &str,
+    fences: &[Range<usize>],
+) -> Option<usize> {
+    let mut from = from;
+    loop {
+        let i = from + document[from..].find(needle)?;
+        match fences.iter().find(|fence| fence.contains(&i)) {
+            Some(fence) => from = fence.end,
+            None => return Some(i),
+        }
+    }
+}
+
+/// Byte ranges of the ``` and ~~~ fenced code blocks of a Markdown
+/// document; an unclosed fence runs to the end of the document.
+fn fenced_regions(document: &str) -> Vec<Range<usize>> {
+    let mut regions = vec![];
+    let mut open: Option<(usize, char, usize)> = None;
+    let mut offset = 0;
+    for line in document.split_inclusive('\n') {
+        let trimmed = line.trim_start_matches(' ');
+        let fence = trimmed
+            .chars()
+            .next()
+            .filter(|c| (*c == '`' || *c == '~') && line.len() - trimmed.len() < 4)
+            .map(|c| (c, trimmed.chars().take_while(|x| *x == c).count()))
+            .filter(|(_, len)| *len >= 3);
+        match (open, fence) {
+            (None, Some((c, len))) => open = Some((offset, c, len)),
+            (Some((start, c, len)), Some((close, close_len)))
+                if close == c && close_len >= len && trimmed.trim_end().len() == close_len =>
+            {
+                regions.push(start..offset + line.len());
+                open = None;
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    if let Some((start, _, _)) = open {
+        regions.push(start..document.len());
+    }
+    regions
+}
+
+fn marker_level(attributes: &str) -> Result<Level, Box<dyn Error>> {
+    for attribute in attributes.split_whitespace() {
+        if let Some((key, value)) = attribute.split_once('=') {
+            if key == "level" {
+                return Ok(value.trim_matches('"').parse::<Level>()?);
+            }
+        }
+    }
+    Err(format!("logdoc:start marker without level: {}", attributes.trim()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn inject_blocks() {
+        let document = r#"# Operations
+
+Intro text.
+
+<!-- logdoc:start level=warn -->
+old table
+<!-- logdoc:end -->
+
+Middle text.
+
+<!-- logdoc:start level="fatal" -->
+<!-- logdoc:end -->
+Trailer.
+"#;
+        let result = inject(document, |level| Ok(format!("\n{level} table\n\n"))).unwrap();
+        assert_eq!(
+            result,
+            r#"# Operations
+
+Intro text.
+
+<!-- logdoc:start level=warn -->
+warn table
+<!-- logdoc:end -->
+
+Middle text.
+
+<!-- logdoc:start level="fatal" -->
+fatal table
+<!-- logdoc:end -->
+Trailer.
+"#
+        );
+        let again = inject(&result, |level| Ok(format!("{level} table"))).unwrap();
+        assert_eq!(result, again);
+    }
+
+    #[test]
+    fn inject_skips_fenced_code() {
+        let document = r#"Example:
+
+```markdown
+<!-- logdoc:start level=warn -->
+<!-- logdoc:end -->
+```
+
+~~~~
+<!-- logdoc:start level=info -->
+~~~
+~~~~
+
+<!-- logdoc:start level=fatal -->
+````
+<!-- logdoc:end -->
+````
+<!-- logdoc:end -->
+"#;
+        let result = inject(document, |level| Ok(format!("{level} table"))).unwrap();
+        let expected = document.replace(
+            "fatal -->\n````\n<!-- logdoc:end -->\n````\n",
+            "fatal -->\nfatal table\n",
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn inject_errors() {
+        let unterminated = "<!-- logdoc:start level=warn -->\ntext\n";
+        assert!(inject(unterminated, |_| Ok(String::new())).is_err());
+        let no_level = "<!-- logdoc:start -->\n<!-- logdoc:end -->\n";
+        assert!(inject(no_level, |_| Ok(String::new())).is_err());
+        let unknown_level = "<!-- logdoc:start level=error -->\n<!-- logdoc:end -->\n";
+        assert!(inject(unknown_level, |_| Ok(String::new())).is_err());
+
+        for save_type in ["csv", "json", "man"] {
+            let arg = crate::args::Arg::try_parse_from([
+                "logdoc",
+                "-p",
+                "p",
+                "-l",
+                "golang",
+                "--save-type",
+                save_type,
+                "--inject",
+                "docs.md",
+            ])
+            .unwrap();
+            let error = crate::app::Application::outputs(&arg, &[]).unwrap_err();
+            assert!(error.to_string().contains("save type"));
+        }
+    }
+}
@@ -8,21 +8,142 @@ pub enum Language {
     Python,
     Java,
     JavaScript,
+    TypeScript,
+    Tsx,
+    CSharp,
+    Kotlin,
+    Php,
+    Bash,
+    Lua,
     Ruby,
     Rust,
 }
 
-#[derive(Debug, Clone)]
-pub enum Comment {
-    Dash,
-    Slash,
+/// Comment syntax of a language: line comment prefixes and block comment
+/// delimiters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Comment {
+    /// Line comment prefixes, the first one is the usual one.
+    pub line: &'static [&'static str],
+    pub block: &'static [(&'static str, &'static str)],
+}
+
+/// Text of one comment line without the comment syntax.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommentLine {
+    pub text: String,
+    /// Written as a doc comment: `///`, `//!`, `/**`, `##`...
+    pub doc: bool,
 }
 
 impl Comment {
+    /// C family: `//` and `/* */`.
+    pub const SLASH: Comment = Comment {
+        line: &["//"],
+        block: &[("/*", "*/")],
+    };
+    /// Python, Ruby and shell: `#`.
+    pub const DASH: Comment = Comment {
+        line: &["#"],
+        block: &[],
+    };
+    pub const PHP: Comment = Comment {
+        line: &["//", "#"],
+        block: &[("/*", "*/")],
+    };
+    /// Lua: `--` and `--[[ ]]`.
+    pub const LUA: Comment = Comment {
+        line: &["--"],
+        block: &[("--[[", "]]")],
+    };
+
     pub fn variants() -> Vec<Self> {
-        vec![Self::Dash, Self::Slash]
+        vec![Self::SLASH, Self::DASH, Self::PHP, Self::LUA]
+    }
+
+    /// Split the text of a comment node into lines without comment syntax.
+    /// Repeated prefix characters and `!` after the prefix mark doc comments
+    /// (`///`, `;;;`, `//!`, `#!`); ` * ` decoration of block comment lines
+    /// is dropped as well.
+    pub fn normalize(&self, text: &str) -> Vec<CommentLine> {
+        let trimmed = text.trim();
+        let block = self
+            .block
+            .iter()
+            .filter(|(open, _)| trimmed.starts_with(open))
+            .max_by_key(|(open, _)| open.len());
+        if let Some((open, close)) = block {
+            let body = &trimmed[open.len()..];
+            let body = body.strip_suffix(close).unwrap_or(body);
+            let (body, doc) = strip_doc_marker(body, open.chars().last());
+            let mut lines: Vec<CommentLine> = body
+                .lines()
+                .map(|line| {
+                    let line = line.trim();
+                    let line = match line.strip_prefix('*') {
+                        Some(rest) if !rest.starts_with('*') => rest,
+                        _ => line,
+                    };
+                    CommentLine {
+                        text: line.trim().to_owned(),
+                        doc,
+                    }
+                })
+                .collect();
+            if lines.first().is_some_and(|l| l.text.is_empty()) {
+                lines.remove(0);
+            }
+            if lines.last().is_some_and(|l| l.text.is_empty()) {
+                lines.pop();
+            }
+            return lines;
+        }
+        trimmed
+            .lines()
+            .map(|line| self.normalize_line(line))
+            .collect()
+    }
+
+    fn normalize_line(&self, line: &str) -> CommentLine {
+        let line = line.trim();
+        let prefix = self
+            .line
+            .iter()
+            .filter(|prefix| line.starts_with(*prefix))
+            .max_by_key(|prefix| prefix.len());
+        match prefix {
+            Some(prefix) => {
+                let (rest, doc) = strip_doc_marker(&line[prefix.len()..], prefix.chars().last());
+                CommentLine {
+                    text: rest.trim().to_owned(),
+                    doc,
+                }
+            }
+            None => CommentLine {
+                text: line.to_owned(),
+                doc: false,
+            },
+        }
+    }
+}
+
+/// Drop repetitions of the last prefix character and a `!` after it.
+fn strip_doc_marker(text: &str, repeated: Option<char>) -> (&str, bool) {
+    let mut text = text;
+    let mut doc = false;
+    if let Some(c) = repeated {
+        while let Some(rest) = text.strip_prefix(c) {
+            text = rest;
+            doc = true;
+        }
     }
+    if let Some(rest) = text.strip_prefix('!') {
+        text = rest;
+        doc = true;
+    }
+    (text, doc)
 }
+
 impl From<&crate::args::Language> for Language {
     fn from(value: &crate::args::Language) -> Self {
         use crate::args;
@@ -33,19 +154,23 @@ impl From<&crate::args::Language> for Language {
             args::Language::Python => Self::Python,
             args::Language::Java => Self::Java,
             args::Language::JavaScript => Self::JavaScript,
+            args::Language::TypeScript => Self::TypeScript,
+            args::Language::Tsx => Self::Tsx,
+            args::Language::CSharp => Self::CSharp,
+            args::Language::Kotlin => Self::Kotlin,
+            args::Language::Php => Self::Php,
+            args::Language::Bash => Self::Bash,
+            args::Language::Lua => Self::Lua,
             args::Language::Ruby => Self::Ruby,
             args::Language::Rust => Self::Rust,
         }
     }
 }
 
+/// The usual line comment prefix.
 impl Display for Comment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use Comment::*;
-        match self {
-            Dash => write!(f, "#"),
-            Slash => write!(f, "//"),
-        }
+        write!(f, "{}", self.line.first().unwrap_or(&""))
     }
 }
 
@@ -59,6 +184,13 @@ impl Display for Language {
             Python => write!(f, "python"),
             Java => write!(f, "java"),
             JavaScript => write!(f, "javascript"),
+            TypeScript => write!(f, "typescript"),
+            Tsx => write!(f, "tsx"),
+            CSharp => write!(f, "c#"),
+            Kotlin => write!(f, "kotlin"),
+            Php => write!(f, "php"),
+            Bash => write!(f, "bash"),
+            Lua => write!(f, "lua"),
             Ruby => write!(f, "ruby"),
             Rust => write!(f, "rust"),
         }
@@ -77,43 +209,197 @@ impl Language {
             Python => query_python(),
             Java => query_java(),
             JavaScript => query_javascript(),
+            TypeScript | Tsx => query_typescript(),
+            CSharp => query_csharp(),
+            Kotlin => query_kotlin(),
+            Php => query_php(),
+            Bash => query_bash(),
+            Lua => query_lua(),
         }
     }
     pub fn sitter_language(&self) -> tree_sitter::Language {
         use Language::*;
         match self {
-            Golang => tree_sitter_go::language(),
-            C => tree_sitter_c::language(),
-            Cpp => tree_sitter_cpp::language(),
-            Python => tree_sitter_python::language(),
-            Java => tree_sitter_java::language(),
-            JavaScript => tree_sitter_javascript::language(),
-            Ruby => tree_sitter_ruby::language(),
-            Rust => tree_sitter_rust::language(),
+            Golang => tree_sitter_go::LANGUAGE.into(),
+            C => tree_sitter_c::LANGUAGE.into(),
+            Cpp => tree_sitter_cpp::LANGUAGE.into(),
+            Python => tree_sitter_python::LANGUAGE.into(),
+            Java => tree_sitter_java::LANGUAGE.into(),
+            JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
+            Kotlin => tree_sitter_kotlin_ng::LANGUAGE.into(),
+            Php => tree_sitter_php::LANGUAGE_PHP.into(),
+            Bash => tree_sitter_bash::LANGUAGE.into(),
+            Lua => tree_sitter_lua::LANGUAGE.into(),
+            Ruby => tree_sitter_ruby::LANGUAGE.into(),
+            Rust => tree_sitter_rust::LANGUAGE.into(),
         }
     }
     pub fn comment(&self) -> Comment {
         use Language::*;
         match self {
-            Golang | C | Cpp | Java | JavaScript | Rust => Comment::Slash,
-            Python | Ruby => Comment::Dash,
+            Golang | C | Cpp | Java | JavaScript | TypeScript | Tsx | CSharp | Kotlin | Rust => {
+                Comment::SLASH
+            }
+            Python | Ruby | Bash => Comment::DASH,
+            Php => Comment::PHP,
+            Lua => Comment::LUA,
         }
     }
 }
 
+impl Language {
+    /// Package, module or namespace of a source file: from the package or
+    /// namespace declaration, for Python and Rust from the file path.
+    pub fn module(&self, root: tree_sitter::Node, source: &[u8], file: &str) -> Option<String> {
+        use Language::*;
+        let declarations: &[&str] = match self {
+            Golang => &["package_clause"],
+            Java => &["package_declaration"],
+            Kotlin => &["package_header"],
+            CSharp => &["file_scoped_namespace_declaration", "namespace_declaration"],
+            Php => &["namespace_definition"],
+            Python => {
+                return module_path(file, "py", false)
+                    .filter(|parts| !parts.is_empty())
+                    .map(|parts| parts.join("."))
+            }
+            Rust => {
+                return module_path(file, "rs", true).map(|mut parts| {
+                    parts.insert(0, "crate".to_owned());
+                    parts.join("::")
+                })
+            }
+            _ => &[],
+        };
+        let mut cursor = root.walk();
+        let declaration = root
+            .named_children(&mut cursor)
+            .find(|node| declarations.contains(&node.kind()))?;
+        let name = declaration
+            .child_by_field_name("name")
+            .or(declaration.named_child(0))?;
+        name.utf8_text(source).ok().map(|name| name.to_owned())
+    }
+
+    /// Node kinds of function calls that may be logging calls.
+    pub fn call_kinds(&self) -> &'static [&'static str] {
+        use Language::*;
+        match self {
+            Golang | C | Cpp | JavaScript | TypeScript | Tsx | Kotlin => &["call_expression"],
+            Rust => &["call_expression", "macro_invocation"],
+            Python => &["call"],
+            Java => &["method_invocation"],
+            CSharp => &["invocation_expression"],
+            Php => &[
+                "function_call_expression",
+                "member_call_expression",
+                "nullsafe_member_call_expression",
+                "scoped_call_expression",
+            ],
+            Bash => &["redirected_statement", "command"],
+            Lua => &["function_call"],
+            Ruby => &["call"],
+        }
+    }
+
+    /// Node kinds of string literals holding log messages.
+    pub fn string_kinds(&self) -> &'static [&'static str] {
+        use Language::*;
+        match self {
+            Golang => &["interpreted_string_literal", "raw_string_literal"],
+            C | Cpp => &["string_literal", "raw_string_literal"],
+            Python => &["string"],
+            Java => &["string_literal"],
+            JavaScript | TypeScript | Tsx => &["string", "template_string"],
+            CSharp => &[
+                "string_literal",
+                "verbatim_string_literal",
+                "raw_string_literal",
+                "interpolated_string_expression",
+            ],
+            Kotlin => &["string_literal", "multiline_string_literal"],
+            Php => &["string", "encapsed_string", "heredoc", "nowdoc"],
+            Bash => &["string", "raw_string", "word"],
+            Lua => &["string"],
+            Ruby => &["string"],
+            Rust => &["string_literal", "raw_string_literal"],
+        }
+    }
+
+    /// File extensions picked up when walking directories.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        use Language::*;
+        match self {
+            Golang => &["go"],
+            C => &["c", "h"],
+            Cpp => &["cpp", "cc", "cxx", "hpp", "hh", "hxx", "h"],
+            Python => &["py"],
+            Java => &["java"],
+            JavaScript => &["js", "mjs", "cjs", "jsx"],
+            TypeScript => &["ts", "mts", "cts"],
+            Tsx => &["tsx"],
+            CSharp => &["cs"],
+            Kotlin => &["kt", "kts"],
+            Php => &["php"],
+            Bash => &["sh", "bash"],
+            Lua => &["lua"],
+            Ruby => &["rb"],
+            Rust => &["rs"],
+        }
+    }
+}
+
+/// Module path of a file relative to the last `src` directory:
+/// `src/db/pool.rs` is `db::pool`; `mod`, `lib`, `main` and `__init__`
+/// files name their directory. With `in_src`, files outside any `src`
+/// directory have no module.
+fn module_path(file: &str, extension: &str, in_src: bool) -> Option<Vec<String>> {
+    let path = std::path::Path::new(file);
+    if path.extension()? != extension {
+        return None;
+    }
+    let mut parts: Vec<String> = path
+        .with_extension("")
+        .components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    match parts.iter().rposition(|p| p == "src") {
+        Some(src) => drop(parts.drain(..=src)),
+        None if in_src => return None,
+        None => {}
+    }
+    if parts
+        .last()
+        .is_some_and(|p| matches!(p.as_str(), "mod" | "lib" | "main" | "__init__"))
+    {
+        parts.pop();
+    }
+    Some(parts)
+}
+
 fn query_go() -> &'static str {
     r#"(
 	(
     	(
     		(comment) @severity
     	)
-        (#match? @severity "^//(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Tt][Rr][Aa][Cc][Ee]|[Ww][Aa][Rr][Nn]|[Ff][Aa][Tt][Aa][Ll]):")
+        (#match? @severity "^//[/!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Tt][Rr][Aa][Cc][Ee]|[Ww][Aa][Rr][Nn]|[Ff][Aa][Tt][Aa][Ll]):")
     )
    	.
     (comment) @subject
     .
     (comment)*? @description
-    )"#
+    )
+(
+    (comment) @severity
+    (#match? @severity "^/\\*[\\s*!]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
 }
 
 fn query_rust() -> &'static str {
@@ -122,13 +408,17 @@ fn query_rust() -> &'static str {
     	(
     		(line_comment) @level
     	)
-        (#match? @level "^//(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+        (#match? @level "^//[/!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
     )
    	.
     (line_comment) @subject
     .
     (line_comment)*? @description
-    )"#
+    )
+(
+    (block_comment) @level
+    (#match? @level "^/\\*[\\s*!]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
 }
 
 fn query_c() -> &'static str {
@@ -137,13 +427,17 @@ fn query_c() -> &'static str {
     	(
     		(comment) @level
     	)
-        (#match? @level "^//(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+        (#match? @level "^//[/!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
     )
    	.
     (comment) @subject
     .
     (comment)*? @description
-    )"#
+    )
+(
+    (comment) @level
+    (#match? @level "^/\\*[\\s*!]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
 }
 
 fn query_cpp() -> &'static str {
@@ -152,13 +446,17 @@ fn query_cpp() -> &'static str {
     	(
     		(comment) @level
     	)
-        (#match? @level "^//(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+        (#match? @level "^//[/!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
     )
    	.
     (comment) @subject
     .
     (comment)*? @description
-    )"#
+    )
+(
+    (comment) @level
+    (#match? @level "^/\\*[\\s*!]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
 }
 
 fn query_ruby() -> &'static str {
@@ -167,7 +465,7 @@ fn query_ruby() -> &'static str {
     	(
     		(comment) @level
     	)
-        (#match? @level "^#(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+        (#match? @level "^#[#!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
     )
    	.
     (comment) @subject
@@ -182,13 +480,19 @@ fn query_python() -> &'static str {
     	(
     		(comment) @level
     	)
-        (#match? @level "^#(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+        (#match? @level "^#[#!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
     )
    	.
     (comment) @subject
     .
     (comment)*? @description
-    )"#
+    )
+(
+    (expression_statement
+        (string
+            (string_content) @level))
+    (#match? @level "(^|\n)[ \t]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
 }
 fn query_java() -> &'static str {
     r#"(
@@ -196,13 +500,17 @@ fn query_java() -> &'static str {
     	(
     		(line_comment) @level
     	)
-        (#match? @level "^//(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+        (#match? @level "^//[/!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
     )
    	.
     (line_comment) @subject
     .
     (line_comment)*? @description
-    )"#
+    )
+(
+    (block_comment) @level
+    (#match? @level "^/\\*[\\s*!]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
 }
 
 fn query_javascript() -> &'static str {
@@ -211,7 +519,121 @@ fn query_javascript() -> &'static str {
     	(
     		(comment) @level
     	)
-        (#match? @level "^//(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+        (#match? @level "^//[/!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+    )
+   	.
+    (comment) @subject
+    .
+    (comment)*? @description
+    )
+(
+    (comment) @level
+    (#match? @level "^/\\*[\\s*!]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
+}
+
+fn query_typescript() -> &'static str {
+    r#"(
+	(
+    	(
+    		(comment) @level
+    	)
+        (#match? @level "^//[/!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+    )
+   	.
+    (comment) @subject
+    .
+    (comment)*? @description
+    )
+(
+    (comment) @level
+    (#match? @level "^/\\*[\\s*!]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
+}
+
+fn query_csharp() -> &'static str {
+    r#"(
+	(
+    	(
+    		(comment) @level
+    	)
+        (#match? @level "^///?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+    )
+   	.
+    (comment) @subject
+    .
+    (comment)*? @description
+    )
+(
+    (comment) @level
+    (#match? @level "^/\\*[\\s*!]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
+}
+
+fn query_kotlin() -> &'static str {
+    r#"(
+	(
+    	(
+    		(line_comment) @level
+    	)
+        (#match? @level "^//[/!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+    )
+   	.
+    (line_comment) @subject
+    .
+    (line_comment)*? @description
+    )
+(
+    (block_comment) @level
+    (#match? @level "^/\\*[\\s*!]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
+}
+
+fn query_lua() -> &'static str {
+    r#"(
+	(
+    	(
+    		(comment) @level
+    	)
+        (#match? @level "^--[-!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+    )
+   	.
+    (comment) @subject
+    .
+    (comment)*? @description
+    )
+(
+    (comment) @level
+    (#match? @level "^--\\[\\[[\\s*!]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
+}
+
+fn query_php() -> &'static str {
+    r#"(
+	(
+    	(
+    		(comment) @level
+    	)
+        (#match? @level "^(//[/!]?|#[#!]?)(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+    )
+   	.
+    (comment) @subject
+    .
+    (comment)*? @description
+    )
+(
+    (comment) @level
+    (#match? @level "^/\\*[\\s*!]*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
+)"#
+}
+
+fn query_bash() -> &'static str {
+    r#"(
+	(
+    	(
+    		(comment) @level
+    	)
+        (#match? @level "^#[#!]?(\\s)*([Ii][Nn][Ff][Oo]|[Dd][Ee][Bb][Uu][Gg]|[Ww][Aa][Rr][Nn]|[Tt][Rr][Aa][Cc][Ee]|[Ff][Aa][Tt][Aa][Ll]):")
     )
    	.
     (comment) @subject
@@ -219,3 +641,407 @@ fn query_javascript() -> &'static str {
     (comment)*? @description
     )"#
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::app::Application;
+    use crate::meta::Level;
+
+    use super::{Comment, CommentLine, Language};
+
+    fn collect(lang: Language, file: &str) -> Vec<(Level, String, String)> {
+        Application::collect_files(&lang, &[file.to_owned()])
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.level, m.message.format(), m.subject.format()))
+            .collect()
+    }
+
+    #[test]
+    fn typescript_fixture() {
+        let metas = collect(Language::TypeScript, "tests/typescript/main.ts");
+        assert_eq!(
+            metas,
+            vec![
+                (
+                    Level::Info,
+                    "service started".to_owned(),
+                    "service is ready to accept requests".to_owned()
+                ),
+                (
+                    Level::Warn,
+                    "user %s not found".to_owned(),
+                    "lookup by id returned nothing".to_owned()
+                ),
+                (
+                    Level::Fatal,
+                    "cannot parse event".to_owned(),
+                    "event payload is not valid JSON".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn tsx_fixture() {
+        let metas = collect(Language::Tsx, "tests/tsx/app.tsx");
+        assert_eq!(
+            metas,
+            vec![
+                (
+                    Level::Debug,
+                    "button clicked".to_owned(),
+                    "user pressed the main button".to_owned()
+                ),
+                (
+                    Level::Trace,
+                    "profile rendered".to_owned(),
+                    "render of the profile card".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn csharp_fixture() {
+        let metas =
+            Application::collect_files(&Language::CSharp, &["tests/csharp/Program.cs".to_owned()])
+                .unwrap();
+        assert_eq!(metas.len(), 2);
+        assert_eq!(metas[0].level, Level::Warn);
+        assert_eq!(metas[0].message.format(), "order {OrderId} not found");
+        assert_eq!(metas[0].subject.format(), "lookup returned no order");
+        assert_eq!(
+            metas[0].description.format(),
+            "the client may use a stale link"
+        );
+        let call = metas[0].call.as_ref().unwrap();
+        assert_eq!(call.function, "_logger.LogWarning");
+        assert_eq!(call.message.as_deref(), Some("Order {OrderId} not found"));
+        assert_eq!(metas[1].level, Level::Fatal);
+        let call = metas[1].call.as_ref().unwrap();
+        assert_eq!(call.function, "Log.Fatal");
+        assert_eq!(
+            call.message.as_deref(),
+            Some("Payment gateway is unreachable")
+        );
+    }
+
+    #[test]
+    fn kotlin_fixture() {
+        let metas = Application::collect_files(
+            &Language::Kotlin,
+            &["tests/kotlin/OrderService.kt".to_owned()],
+        )
+        .unwrap();
+        assert_eq!(metas.len(), 2);
+        assert_eq!(metas[0].level, Level::Warn);
+        assert_eq!(metas[0].message.format(), "order %s not found");
+        assert_eq!(metas[0].subject.format(), "lookup returned no order");
+        assert_eq!(metas[0].module.as_deref(), Some("shop.orders"));
+        let call = metas[0].call.as_ref().unwrap();
+        assert_eq!(call.function, "logger.warn");
+        assert_eq!(call.message.as_deref(), Some("Order $id not found"));
+        assert_eq!(call.line, 12);
+        assert_eq!(metas[1].level, Level::Fatal);
+        assert_eq!(metas[1].subject.format(), "orders can't be paid");
+        assert_eq!(metas[1].description.format(), "check gateway credentials");
+        let call = metas[1].call.as_ref().unwrap();
+        assert_eq!(call.function, "logger.error");
+        assert_eq!(
+            call.message.as_deref(),
+            Some("Payment gateway is unreachable")
+        );
+    }
+
+    #[test]
+    fn lua_fixture() {
+        let metas =
+            Application::collect_files(&Language::Lua, &["tests/lua/worker.lua".to_owned()])
+                .unwrap();
+        assert_eq!(metas.len(), 2);
+        assert_eq!(metas[0].level, Level::Warn);
+        assert_eq!(metas[0].message.format(), "queue %s is empty");
+        assert_eq!(metas[0].subject.format(), "nothing to process");
+        assert_eq!(metas[0].description.format(), "producers may be stopped");
+        let call = metas[0].call.as_ref().unwrap();
+        assert_eq!(call.function, "log.warn");
+        assert_eq!(call.message.as_deref(), Some("queue %s is empty"));
+        assert_eq!(metas[1].level, Level::Fatal);
+        assert_eq!(metas[1].subject.format(), "jobs are lost");
+        assert_eq!(metas[1].description.format(), "check the redis connection");
+        let call = metas[1].call.as_ref().unwrap();
+        assert_eq!(call.function, "ngx.log");
+        assert_eq!(call.message.as_deref(), Some("job store is unavailable"));
+    }
+
+    #[test]
+    fn php_fixture() {
+        let metas = Application::collect_files(&Language::Php, &["tests/php/index.php".to_owned()])
+            .unwrap();
+        assert_eq!(metas.len(), 3);
+        assert_eq!(metas[0].level, Level::Warn);
+        assert_eq!(metas[0].subject.format(), "invoice skipped");
+        let call = metas[0].call.as_ref().unwrap();
+        assert_eq!(call.function, "$this->logger->warning");
+        assert_eq!(metas[1].level, Level::Fatal);
+        assert_eq!(
+            metas[1].message.format(),
+            "payment provider rejected the api key"
+        );
+        assert_eq!(metas[1].subject.format(), "billing is stopped");
+        let call = metas[1].call.as_ref().unwrap();
+        assert_eq!(call.function, "Log::error");
+        assert_eq!(
+            call.message.as_deref(),
+            Some("Payment provider rejected the API key")
+        );
+        let call = metas[2].call.as_ref().unwrap();
+        assert_eq!(call.function, "error_log");
+        assert_eq!(call.message.as_deref(), Some("billing worker started"));
+    }
+
+    #[test]
+    fn shell_fixture() {
+        let metas =
+            Application::collect_files(&Language::Bash, &["tests/shell/backup.sh".to_owned()])
+                .unwrap();
+        assert_eq!(metas.len(), 2);
+        assert_eq!(metas[0].level, Level::Info);
+        assert_eq!(metas[0].subject.format(), "nightly backup is running");
+        let call = metas[0].call.as_ref().unwrap();
+        assert_eq!(call.function, "logger -p user.info");
+        assert_eq!(call.message.as_deref(), Some("backup started"));
+        assert_eq!(metas[1].level, Level::Fatal);
+        assert_eq!(
+            metas[1].description.format(),
+            "check free space on the backup volume"
+        );
+        let call = metas[1].call.as_ref().unwrap();
+        assert_eq!(call.function, "echo >&2");
+        assert_eq!(
+            call.message.as_deref(),
+            Some("ERROR: backup archive can't be written")
+        );
+    }
+
+    fn texts(comment: Comment, text: &str) -> Vec<(String, bool)> {
+        comment
+            .normalize(text)
+            .into_iter()
+            .map(|CommentLine { text, doc }| (text, doc))
+            .collect()
+    }
+
+    #[test]
+    fn normalize_comments() {
+        let line = |text: &str, doc| vec![(text.to_owned(), doc)];
+        assert_eq!(
+            texts(Comment::SLASH, "// warn: x\n"),
+            line("warn: x", false)
+        );
+        assert_eq!(texts(Comment::SLASH, "/// warn: x"), line("warn: x", true));
+        assert_eq!(texts(Comment::SLASH, "//! warn: x"), line("warn: x", true));
+        assert_eq!(texts(Comment::DASH, "#! warn: x"), line("warn: x", true));
+        assert_eq!(texts(Comment::DASH, "## warn: x"), line("warn: x", true));
+        assert_eq!(texts(Comment::PHP, "# warn: x"), line("warn: x", false));
+        assert_eq!(texts(Comment::PHP, "// warn: x"), line("warn: x", false));
+        assert_eq!(
+            texts(Comment::LUA, "--[[ warn: x ]]"),
+            line("warn: x", false)
+        );
+        assert_eq!(texts(Comment::LUA, "--- warn: x"), line("warn: x", true));
+        assert_eq!(
+            texts(
+                Comment::SLASH,
+                "/**\n * warn: x\n * subject\n *\n * **bold**\n */"
+            ),
+            vec![
+                ("warn: x".to_owned(), true),
+                ("subject".to_owned(), true),
+                ("".to_owned(), true),
+                ("**bold**".to_owned(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn c_block_comments() {
+        let metas = collect(Language::C, "tests/c/main.c");
+        assert_eq!(
+            metas,
+            vec![
+                (
+                    Level::Warn,
+                    "config %s not found".to_owned(),
+                    "defaults are used".to_owned()
+                ),
+                (Level::Fatal, "out of memory".to_owned(), "".to_owned()),
+                (
+                    Level::Info,
+                    "daemon started".to_owned(),
+                    "daemon is running".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn python_fixture() {
+        let metas =
+            Application::collect_files(&Language::Python, &["tests/python/main.py".to_owned()])
+                .unwrap();
+        let summary: Vec<(Level, String, String, Option<String>)> = metas
+            .iter()
+            .map(|m| {
+                (
+                    m.level,
+                    m.message.format(),
+                    m.subject.format(),
+                    m.call.as_ref().map(|c| c.function.clone()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    Level::Info,
+                    "test message from python".to_owned(),
+                    "just info message".to_owned(),
+                    Some("print".to_owned())
+                ),
+                (
+                    Level::Fatal,
+                    "fatal message from python".to_owned(),
+                    "emergency, do something".to_owned(),
+                    Some("fatal".to_owned())
+                ),
+                (
+                    Level::Warn,
+                    "payment for order %s declined".to_owned(),
+                    "the card was declined by the bank".to_owned(),
+                    Some("logger.warning".to_owned())
+                ),
+                (
+                    Level::Fatal,
+                    "database is unreachable".to_owned(),
+                    "the service can't store data".to_owned(),
+                    Some("logging.exception".to_owned())
+                ),
+                (
+                    Level::Debug,
+                    "config reloaded".to_owned(),
+                    "new config is applied".to_owned(),
+                    Some("log.debug".to_owned())
+                ),
+            ]
+        );
+        assert_eq!(
+            metas[2].description.format(),
+            "ask the customer for another card"
+        );
+        assert_eq!(metas[2].line, 20);
+        // the Warn: section of migrate() documents the function and
+        // `# WARNING:` comments are no annotations
+        assert!(metas
+            .iter()
+            .all(|m| m.message.format() != "locks the tables"));
+        let call = metas[3].call.as_ref().unwrap();
+        assert_eq!(call.level(), Some(Level::Fatal));
+        let call = metas[2].call.as_ref().unwrap();
+        assert_eq!(call.level(), Some(Level::Warn));
+        assert_eq!(metas[2].call.as_ref().unwrap().fields, vec!["order"]);
+        assert_eq!(metas[4].call.as_ref().unwrap().fields, vec!["path"]);
+    }
+
+    #[test]
+    fn rust_fixture() {
+        let metas =
+            Application::collect_files(&Language::Rust, &["tests/rust/service.rs".to_owned()])
+                .unwrap();
+        assert_eq!(metas.len(), 3);
+
+        assert_eq!(metas[0].level, Level::Warn);
+        assert_eq!(metas[0].message.format(), "connection lost: {}");
+        let call = metas[0].call.as_ref().unwrap();
+        assert_eq!(call.function, "warn");
+        assert_eq!(call.target.as_deref(), Some("db"));
+        assert_eq!(call.fields, vec!["user_id"]);
+        assert_eq!(call.message.as_deref(), Some("connection lost: {}"));
+        assert_eq!(call.span, None);
+
+        assert_eq!(metas[1].level, Level::Fatal);
+        assert_eq!(metas[1].subject.format(), "the order is not charged");
+        let call = metas[1].call.as_ref().unwrap();
+        assert_eq!(call.function, "tracing::error");
+        assert_eq!(call.fields, vec!["user.id", "err"]);
+        assert_eq!(call.message.as_deref(), Some("payment failed"));
+        assert_eq!(call.span.as_deref(), Some("charge"));
+
+        assert_eq!(metas[2].level, Level::Info);
+        let call = metas[2].call.as_ref().unwrap();
+        assert_eq!(call.function, "slog::info");
+        assert_eq!(call.message.as_deref(), Some("service started"));
+        assert_eq!(call.fields, vec!["port", "tls"]);
+    }
+
+    #[test]
+    fn go_structured_fixture() {
+        let metas =
+            Application::collect_files(&Language::Golang, &["tests/go/structured.go".to_owned()])
+                .unwrap();
+        let calls: Vec<(String, Option<String>, Vec<String>)> = metas
+            .iter()
+            .map(|m| {
+                let call = m.call.clone().unwrap();
+                (call.function, call.message, call.fields)
+            })
+            .collect();
+        let call = |function: &str, message: &str, fields: &[&str]| {
+            (
+                function.to_owned(),
+                Some(message.to_owned()),
+                fields.iter().map(|f| f.to_string()).collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(
+            calls,
+            vec![
+                call("slog.Warn", "charge failed", &["order", "attempt"]),
+                call("logger.Info", "request served", &["url", "error"]),
+                call("log.Debug", "login failed", &["user", "attempt", "error"]),
+                call("logrus.Fatalf", "cannot open %s", &["order", "user"]),
+                call("logger.Warn", "slow request", &["peer"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn modules() {
+        assert_eq!(
+            super::module_path("app/src/db/pool.rs", "rs", true),
+            Some(vec!["db".to_owned(), "pool".to_owned()])
+        );
+        assert_eq!(super::module_path("src/lib.rs", "rs", true), Some(vec![]));
+        assert_eq!(
+            super::module_path("./shop/orders/__init__.py", "py", false),
+            Some(vec!["shop".to_owned(), "orders".to_owned()])
+        );
+        assert_eq!(super::module_path("main.go", "py", false), None);
+        assert_eq!(super::module_path("tests/it.rs", "rs", true), None);
+
+        let metas =
+            Application::collect_files(&Language::Golang, &["tests/go/structured.go".to_owned()])
+                .unwrap();
+        assert_eq!(metas[0].module.as_deref(), Some("billing"));
+        let metas =
+            Application::collect_files(&Language::Rust, &["tests/rust/service.rs".to_owned()])
+                .unwrap();
+        assert_eq!(metas[0].module, None);
+        let metas =
+            Application::collect_files(&Language::CSharp, &["tests/csharp/Program.cs".to_owned()])
+                .unwrap();
+        assert_eq!(metas[0].module.as_deref(), Some("Shop.Orders"));
+    }
+}
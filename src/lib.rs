@@ -1,7 +1,21 @@
+pub mod alerts;
 pub mod app;
 pub mod args;
+pub mod baseline;
+pub mod calls;
+pub mod codegen;
+pub mod coverage;
+pub mod duplicates;
+pub mod explain;
 pub mod files;
+pub mod findings;
+pub mod i18n;
+pub mod inject;
 pub mod language;
 pub mod meta;
+pub mod pattern;
 pub mod queries;
+pub mod show;
+pub mod site;
 pub mod template;
+pub mod workspace;
@@ -5,6 +5,9 @@ fn main() {
     let result = Application::run();
     match result {
         Ok(_) => {}
-        Err(e) => println!("Failed: {e:?}"),
+        Err(e) => {
+            eprintln!("Failed: {e:?}");
+            std::process::exit(1);
+        }
     }
 }
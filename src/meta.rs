@@ -1,8 +1,13 @@
-use std::fmt::Display;
+use std::{collections::BTreeMap, fmt::Display};
 
 use tree_sitter::QueryCapture;
 
-use crate::files;
+use crate::{
+    args::{GroupBy, SortBy},
+    calls::LogCall,
+    files,
+    language::{Comment, CommentLine},
+};
 
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct Message(pub String);
@@ -11,12 +16,43 @@ pub struct Subject(pub String);
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct Description(pub String);
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Meta {
     pub level: Level,
     pub message: Message,
     pub subject: Subject,
     pub description: Description,
+    pub file: String,
+    pub line: usize,
+    pub tags: BTreeMap<String, String>,
+    pub call: Option<LogCall>,
+    /// Structured fields: keys passed by the linked call and fields
+    /// described with `// field user: the account id`.
+    pub fields: Vec<Field>,
+    /// Package, module or namespace of the file (see `Language::module`).
+    pub module: Option<String>,
+    /// `(file, line, call)` of identical entries merged into this one.
+    pub locations: Vec<(String, usize, Option<LogCall>)>,
+    /// Subjects and descriptions annotated as `subject[de]: ...` by locale.
+    pub translations: BTreeMap<String, crate::i18n::Translation>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub description: String,
+}
+
+/// Description lines starting with one of these keys (`// alert: ...`) are
+/// moved from the description into `Meta::tags`.
+pub const TAGS: &[&str] = &["alert", "action", "runbook"];
+
+/// Key and value of a `key: value` line of a known tag.
+fn tag_line(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.split_once(':')?;
+    let key = key.trim().to_lowercase();
+    TAGS.contains(&key.as_str())
+        .then(|| (key, value.trim().to_owned()))
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -34,7 +70,7 @@ pub struct Pos {
     pub end: (u32, u32),
 }
 
-#[derive(Debug, PartialEq, Eq, Default)]
+#[derive(Debug, PartialEq, Eq, Default, Clone, Copy, Hash, PartialOrd, Ord)]
 pub enum Level {
     #[default]
     Info,
@@ -45,6 +81,27 @@ pub enum Level {
 }
 
 impl Level {
+    pub fn all() -> [Level; 5] {
+        [
+            Level::Info,
+            Level::Debug,
+            Level::Trace,
+            Level::Warn,
+            Level::Fatal,
+        ]
+    }
+
+    /// Rank of the level from trace (0) to fatal (4).
+    pub fn severity(&self) -> u8 {
+        match self {
+            Level::Trace => 0,
+            Level::Debug => 1,
+            Level::Info => 2,
+            Level::Warn => 3,
+            Level::Fatal => 4,
+        }
+    }
+
     fn variants(&self) -> Vec<&'static str> {
         use Level::*;
         match self {
@@ -67,6 +124,29 @@ impl Level {
     }
 }
 
+impl std::str::FromStr for Level {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Level::all()
+            .into_iter()
+            .find(|l| l.to_string() == s.trim().to_lowercase())
+            .ok_or(format!("unknown level {s}"))
+    }
+}
+
+impl From<&crate::args::Level> for Level {
+    fn from(value: &crate::args::Level) -> Self {
+        use crate::args;
+        match value {
+            args::Level::Info => Self::Info,
+            args::Level::Debug => Self::Debug,
+            args::Level::Trace => Self::Trace,
+            args::Level::Warn => Self::Warn,
+            args::Level::Fatal => Self::Fatal,
+        }
+    }
+}
+
 impl Display for Level {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let st = match self {
@@ -80,36 +160,161 @@ impl Display for Level {
     }
 }
 
-impl<T> TryFrom<(&String, &T)> for Message
-where
-    T: Display,
-{
+impl TryFrom<&CommentLine> for Message {
     type Error = &'static str;
-    fn try_from(value: (&String, &T)) -> Result<Self, Self::Error> {
-        let mut line = value.0.clone();
-        let level = Level::from((value.0, value.1));
-        if line
-            .to_lowercase()
-            .starts_with(format!("{}", value.1).as_str())
-        {
-            let l = format!("{}", value.1).len();
-            crop_letters(&mut line, l);
-            delete_spaces_dotes(&mut line);
-
-            let level_variants = level.variants();
-            for variant in level_variants {
-                if line.to_lowercase().starts_with(variant) {
-                    let len = variant.len();
-                    crop_letters(&mut line, len);
-                    delete_spaces_dotes(&mut line);
-                    return Ok(Message(line));
-                }
+    fn try_from(value: &CommentLine) -> Result<Self, Self::Error> {
+        let mut line = value.text.clone();
+        let level = Level::from(value);
+        for variant in level.variants() {
+            if line.to_lowercase().starts_with(variant) {
+                crop_letters(&mut line, variant.len());
+                delete_spaces_dotes(&mut line);
+                return Ok(Message(line));
             }
         }
         Err("unexpected")
     }
 }
 
+impl TryFrom<(&String, &Comment)> for Message {
+    type Error = &'static str;
+    fn try_from(value: (&String, &Comment)) -> Result<Self, Self::Error> {
+        Message::try_from(&first_line(value.0, value.1))
+    }
+}
+
+impl Meta {
+    /// Stable identifier of the message, derived from level and message text
+    /// so regenerated catalogs keep the same IDs.
+    pub fn id(&self) -> String {
+        let mut hash: u32 = 0x811c9dc5;
+        let key = format!("{}:{}", self.level, self.message.format());
+        for byte in key.as_bytes() {
+            hash ^= *byte as u32;
+            hash = hash.wrapping_mul(0x01000193);
+        }
+        format!("{:08x}", hash)
+    }
+
+    /// Read `key: value` lines of known tags, which stay in the description,
+    /// and move `field name: text` lines out of it; fields of the linked call
+    /// come first.
+    pub fn extract_tags(&mut self) {
+        let mut description = vec![];
+        let mut fields: Vec<Field> = vec![];
+        for name in self.call.iter().flat_map(|call| call.fields.iter()) {
+            if !fields.iter().any(|f| f.name == *name) {
+                fields.push(Field {
+                    name: name.clone(),
+                    description: String::new(),
+                });
+            }
+        }
+        for line in self.description.0.lines() {
+            let field = line.trim().strip_prefix("field ").and_then(|rest| {
+                let (name, text) = rest.split_once(':')?;
+                Some((name.trim().to_owned(), text.trim().to_owned()))
+            });
+            if let Some((name, text)) = field {
+                match fields.iter_mut().find(|f| f.name == name) {
+                    Some(field) => field.description = text,
+                    None => fields.push(Field {
+                        name,
+                        description: text,
+                    }),
+                }
+                continue;
+            }
+            if let Some((key, locale, text)) = translated_line(line) {
+                let translation = self.translations.entry(locale).or_default();
+                let entry = match key.as_str() {
+                    "subject" => &mut translation.subject,
+                    _ => &mut translation.description,
+                };
+                match entry {
+                    Some(existing) => {
+                        existing.push('\n');
+                        existing.push_str(&text);
+                    }
+                    None => *entry = Some(text),
+                }
+                continue;
+            }
+            if let Some((key, value)) = tag_line(line) {
+                let entry = self.tags.entry(key).or_default();
+                if !entry.is_empty() {
+                    entry.push(' ');
+                }
+                entry.push_str(&value);
+            }
+            description.push(format!("{}\n", line));
+        }
+        self.description = Description(description.join(""));
+        self.fields = fields;
+    }
+
+    pub fn tag(&self, key: &str) -> Option<&String> {
+        self.tags.get(key)
+    }
+
+    /// Description without the lines of known tags, for exporters that
+    /// output the tags on their own.
+    pub fn untagged_description(&self) -> String {
+        self.description
+            .0
+            .lines()
+            .filter(|line| tag_line(line).is_none())
+            .map(|line| format!("{}\n", line))
+            .collect()
+    }
+
+    /// Name of the group of the entry: its module, or the directory of the
+    /// file for languages without one.
+    pub fn group(&self, group_by: &GroupBy) -> Option<String> {
+        let directory = || {
+            std::path::Path::new(&self.file)
+                .parent()
+                .map(|p| p.display().to_string())
+                .filter(|p| !p.is_empty())
+                .unwrap_or(".".to_owned())
+        };
+        match group_by {
+            GroupBy::None => None,
+            GroupBy::Package => Some(self.module.clone().unwrap_or_else(directory)),
+            GroupBy::Directory => Some(directory()),
+        }
+    }
+
+    /// Order entries by `--sort-by`; severity puts fatal entries first.
+    pub fn sort_by(metas: &mut [Meta], order: &SortBy) {
+        let location = |a: &Meta, b: &Meta| a.file.cmp(&b.file).then_with(|| a.line.cmp(&b.line));
+        match order {
+            SortBy::Message => {
+                metas.sort_by(|a, b| a.message.0.cmp(&b.message.0).then_with(|| location(a, b)))
+            }
+            SortBy::Location => metas.sort_by(location),
+            SortBy::Severity => metas.sort_by(|a, b| {
+                b.level
+                    .severity()
+                    .cmp(&a.level.severity())
+                    .then_with(|| a.message.0.cmp(&b.message.0))
+                    .then_with(|| location(a, b))
+            }),
+        }
+    }
+
+    /// Deterministic order of entries: by level, message, then location.
+    pub fn sort(metas: &mut [Meta]) {
+        metas.sort_by(|a, b| {
+            a.level
+                .cmp(&b.level)
+                .then_with(|| a.message.0.cmp(&b.message.0))
+                .then_with(|| a.file.cmp(&b.file))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+    }
+}
+
 impl Message {
     pub fn format(&self) -> String {
         let mut msg = self.0.clone();
@@ -144,57 +349,80 @@ impl Description {
     }
 }
 
-impl<T> From<(&String, &T)> for Subject
-where
-    T: Display,
-{
-    fn from(value: (&String, &T)) -> Self {
-        let mut line = value.0.clone();
-        if line
-            .to_lowercase()
-            .starts_with(format!("{}", value.1).as_str())
-        {
-            let l = format!("{}", value.1).len();
-            crop_letters(&mut line, l);
-            // delete_spaces_dotes(&mut line);
-            delete_spaces(&mut line);
+impl From<&CommentLine> for Subject {
+    fn from(value: &CommentLine) -> Self {
+        let mut line = value.text.clone();
+        if value.doc {
+            strip_xml_tags(&mut line);
         }
         Subject(line)
     }
 }
-impl<T> From<(&String, &T)> for Description
-where
-    T: Display,
-{
-    fn from(value: (&String, &T)) -> Self {
-        let mut line = value.0.clone();
-        if line
-            .to_lowercase()
-            .starts_with(format!("{}", value.1).as_str())
-        {
-            let l = format!("{}", value.1).len();
-            crop_letters(&mut line, l);
-            // delete_spaces_dotes(&mut line);
-            delete_spaces(&mut line);
+
+impl From<(&String, &Comment)> for Subject {
+    fn from(value: (&String, &Comment)) -> Self {
+        Subject::from(&first_line(value.0, value.1))
+    }
+}
+
+impl From<&CommentLine> for Description {
+    fn from(value: &CommentLine) -> Self {
+        let mut line = value.text.clone();
+        if value.doc {
+            strip_xml_tags(&mut line);
         }
         Description(line)
     }
 }
 
-fn delete_spaces(line: &mut String) {
-    loop {
-        if line.starts_with(" ") {
-            crop_letters(line, 1);
-        } else {
-            break;
+impl From<(&String, &Comment)> for Description {
+    fn from(value: (&String, &Comment)) -> Self {
+        Description::from(&first_line(value.0, value.1))
+    }
+}
+
+/// First line of a comment without the comment syntax.
+fn first_line(text: &str, comment: &Comment) -> CommentLine {
+    comment
+        .normalize(text)
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+}
+
+/// Drop XML doc tags (`<summary>`, `</para>`...) from a doc comment line;
+/// lines with nothing but tags become empty.
+fn strip_xml_tags(line: &mut String) {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line.as_str();
+    while let Some(start) = rest.find('<') {
+        match rest[start..].find('>') {
+            Some(end) if is_xml_tag(&rest[start + 1..start + end]) => {
+                out.push_str(&rest[..start]);
+                rest = &rest[start + end + 1..];
+            }
+            _ => {
+                out.push_str(&rest[..=start]);
+                rest = &rest[start + 1..];
+            }
         }
     }
+    out.push_str(rest);
+    if out.trim().is_empty() {
+        out.clear();
+    }
+    *line = out;
 }
+
+fn is_xml_tag(tag: &str) -> bool {
+    let name = tag.trim_start_matches('/').trim_end_matches('/');
+    let name = name.split_whitespace().next().unwrap_or_default();
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphabetic())
+}
+
 fn delete_spaces_dotes(line: &mut String) {
     loop {
-        if line.starts_with(" ") {
-            crop_letters(line, 1);
-        } else if line.starts_with(":") {
+        if line.starts_with(' ') || line.starts_with(':') {
             crop_letters(line, 1);
         } else {
             break;
@@ -213,52 +441,42 @@ fn crop_letters(s: &mut String, pos: usize) {
     }
 }
 
-impl<T> From<(&String, &T)> for Level
-where
-    T: Display,
-{
-    fn from(value: (&String, &T)) -> Self {
-        let comment = format!("{}", value.1);
-        let comment_len = comment.len();
-        let mut line = value.0.to_owned();
-        crop_letters(&mut line, comment_len);
-        delete_spaces(&mut line);
-        if line.to_lowercase().starts_with("info") {
-            crop_letters(&mut line, 4);
-            delete_spaces(&mut line);
-            if line.to_lowercase().starts_with(":") {
-                return Level::Info;
-            }
-        }
-        if line.to_lowercase().starts_with("debug") {
-            crop_letters(&mut line, 5);
-            delete_spaces(&mut line);
-            if line.to_lowercase().starts_with(":") {
-                return Level::Debug;
-            }
-        }
-        if line.to_lowercase().starts_with("trace") {
-            crop_letters(&mut line, 5);
-            delete_spaces(&mut line);
-            if line.to_lowercase().starts_with(":") {
-                return Level::Trace;
-            }
-        }
-        if line.to_lowercase().starts_with("fatal") {
-            crop_letters(&mut line, 5);
-            delete_spaces(&mut line);
-            if line.to_lowercase().starts_with(":") {
-                return Level::Fatal;
-            }
-        }
-        if line.to_lowercase().starts_with("warn") {
-            crop_letters(&mut line, 4);
-            delete_spaces(&mut line);
-            if line.to_lowercase().starts_with(":") {
-                return Level::Warn;
-            }
-        }
-        return Level::Info;
+/// `(key, locale, text)` of a `subject[de]: text` or
+/// `description[de]: text` line.
+fn translated_line(line: &str) -> Option<(String, String, String)> {
+    let (key, text) = line.split_once(':')?;
+    let (name, locale) = key.trim().strip_suffix(']')?.split_once('[')?;
+    let name = name.trim().to_lowercase();
+    if !["subject", "description"].contains(&name.as_str()) || locale.trim().is_empty() {
+        return None;
+    }
+    Some((name, locale.trim().to_owned(), text.trim().to_owned()))
+}
+
+impl Level {
+    /// Level named by an annotation keyword: `warning` is warn, `error` is
+    /// fatal.
+    pub fn from_keyword(keyword: &str) -> Option<Level> {
+        let keyword = format!("{}:", keyword.trim().to_lowercase());
+        Level::all()
+            .into_iter()
+            .find(|level| level.variants().contains(&keyword.as_str()))
+    }
+}
+
+impl From<&CommentLine> for Level {
+    fn from(value: &CommentLine) -> Self {
+        value
+            .text
+            .split_once(':')
+            .and_then(|(keyword, _)| Level::from_keyword(keyword))
+            .unwrap_or_default()
+    }
+}
+
+impl From<(&String, &Comment)> for Level {
+    fn from(value: (&String, &Comment)) -> Self {
+        Level::from(&first_line(value.0, value.1))
     }
 }
 
@@ -280,7 +498,7 @@ impl TryFrom<u8> for Typo {
             0 => Ok(Typo::Level),
             1 => Ok(Typo::Subject),
             2 => Ok(Typo::Description),
-            n @ _ => Err(format!("unsupported value {n}")),
+            n => Err(format!("unsupported value {n}")),
         }
     }
 }
@@ -318,13 +536,11 @@ impl files::WalkInPosition for Pos {
 
 #[cfg(test)]
 mod tests {
-    use crate::language::Comment;
-
     use super::*;
 
     #[test]
     fn check_level_from_string() {
-        let comments = vec![Comment::Slash, Comment::Dash];
+        let comments = Comment::variants();
         let variants: Vec<(Level, Vec<&'static str>)> = vec![
             (Level::Info, Level::Info.variants()),
             (Level::Debug, Level::Debug.variants()),
@@ -336,7 +552,7 @@ mod tests {
             for comment in &comments {
                 for v in &vs {
                     let st = format!("{} {} message", comment, v);
-                    let cur_level = Level::from((&st, &comment));
+                    let cur_level = Level::from((&st, comment));
                     assert_eq!(l, cur_level);
                 }
             }
@@ -365,19 +581,110 @@ mod tests {
         for variant in variants {
             for comment in &comments {
                 let s = format!("{} {} {}", comment, variant, relevant_str);
-                let m = Message::try_from((&s, &comment)).unwrap();
+                let m = Message::try_from((&s, comment)).unwrap();
                 assert_eq!(relevant_message, m);
             }
         }
         for comment in &comments {
             let txt = format!("{} {}", comment, relevant_str);
-            let s = Subject::from((&txt, &comment));
-            let d = Description::from((&txt, &comment));
+            let s = Subject::from((&txt, comment));
+            let d = Description::from((&txt, comment));
             assert_eq!(relevant_subject, s);
             assert_eq!(relevant_desc, d);
         }
     }
 
+    #[test]
+    fn check_extract_tags() {
+        let mut m = Meta {
+            description: Description(
+                "disk is full\nalert: page on-call\nAction: free space\nthen restart\n".to_owned(),
+            ),
+            ..Default::default()
+        };
+        m.extract_tags();
+        assert_eq!(
+            m.description,
+            Description(
+                "disk is full\nalert: page on-call\nAction: free space\nthen restart\n".to_owned()
+            )
+        );
+        assert_eq!(m.untagged_description(), "disk is full\nthen restart\n");
+        assert_eq!(m.tag("alert"), Some(&"page on-call".to_owned()));
+        assert_eq!(m.tag("action"), Some(&"free space".to_owned()));
+        assert_eq!(m.tag("runbook"), None);
+    }
+
+    #[test]
+    fn check_extract_fields() {
+        let mut m = Meta {
+            description: Description(
+                "login failed\nfield user: the account id\nfield ip: client address\n".to_owned(),
+            ),
+            call: Some(LogCall {
+                fields: vec!["attempt".to_owned(), "user".to_owned()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        m.extract_tags();
+        assert_eq!(m.description, Description("login failed\n".to_owned()));
+        let fields: Vec<(&str, &str)> = m
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.description.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("attempt", ""),
+                ("user", "the account id"),
+                ("ip", "client address")
+            ]
+        );
+    }
+
+    #[test]
+    fn check_extract_translations() {
+        let mut m = Meta {
+            description: Description(
+                "disk is full\nsubject[de]: Festplatte\ndescription[de]: Platz schaffen\nDescription[de]: sofort\n"
+                    .to_owned(),
+            ),
+            ..Default::default()
+        };
+        m.extract_tags();
+        assert_eq!(m.description, Description("disk is full\n".to_owned()));
+        let de = &m.translations["de"];
+        assert_eq!(de.subject.as_deref(), Some("Festplatte"));
+        assert_eq!(de.description.as_deref(), Some("Platz schaffen\nsofort"));
+    }
+
+    #[test]
+    fn check_doc_comments() {
+        let l = "/// warn: disk is full".to_owned();
+        assert_eq!(Level::from((&l, &Comment::SLASH)), Level::Warn);
+        assert_eq!(
+            Message::try_from((&l, &Comment::SLASH)),
+            Ok(Message("disk is full".to_owned()))
+        );
+        let s = "/// <summary>no space left</summary>".to_owned();
+        assert_eq!(
+            Subject::from((&s, &Comment::SLASH)),
+            Subject("no space left".to_owned())
+        );
+        let d = "/// </summary>\n".to_owned();
+        assert_eq!(
+            Description::from((&d, &Comment::SLASH)),
+            Description("".to_owned())
+        );
+        let d = "// keep <id> as is".to_owned();
+        assert_eq!(
+            Description::from((&d, &Comment::SLASH)),
+            Description("keep <id> as is".to_owned())
+        );
+    }
+
     #[test]
     fn check_message_from_string() {
         let relevant = "test Message";
@@ -385,40 +692,40 @@ mod tests {
 
         {
             let i1 = format!("// info: {}", relevant);
-            let m1 = Message::try_from((&i1, &Comment::Slash)).unwrap();
+            let m1 = Message::try_from((&i1, &Comment::SLASH)).unwrap();
             assert_eq!(msgrelevant, m1);
         }
         {
             let d1 = format!("//debug: {}", relevant);
             let d2 = format!("// Debug: {}", relevant);
-            let m1 = Message::try_from((&d1, &Comment::Slash)).unwrap();
-            let m2 = Message::try_from((&d2, &Comment::Slash)).unwrap();
+            let m1 = Message::try_from((&d1, &Comment::SLASH)).unwrap();
+            let m2 = Message::try_from((&d2, &Comment::SLASH)).unwrap();
             assert_eq!(msgrelevant, m1);
             assert_eq!(msgrelevant, m2);
         }
         {
             let t2 = format!("//trace: {}", relevant);
-            let m2 = Message::try_from((&t2, &Comment::Slash)).unwrap();
+            let m2 = Message::try_from((&t2, &Comment::SLASH)).unwrap();
             assert_eq!(msgrelevant, m2);
         }
         {
             let w2 = format!("//warn:{}", relevant);
-            let m2 = Message::try_from((&w2, &Comment::Slash)).unwrap();
+            let m2 = Message::try_from((&w2, &Comment::SLASH)).unwrap();
             assert_eq!(msgrelevant, m2);
         }
         {
             let w1 = format!("# TRACE: {}", relevant);
-            let m1 = Message::try_from((&w1, &Comment::Dash)).unwrap();
+            let m1 = Message::try_from((&w1, &Comment::DASH)).unwrap();
             assert_eq!(msgrelevant, m1);
         }
         {
             let f1 = format!("# FATAL: {}", relevant);
-            let m1 = Message::try_from((&f1, &Comment::Dash)).unwrap();
+            let m1 = Message::try_from((&f1, &Comment::DASH)).unwrap();
             assert_eq!(msgrelevant, m1);
         }
         {
             let e1 = format!("# info : {}", relevant);
-            let m = Message::try_from((&e1, &Comment::Dash));
+            let m = Message::try_from((&e1, &Comment::DASH));
             assert_eq!(Err("unexpected"), m);
         }
     }
@@ -0,0 +1,269 @@
+use std::fmt::Display;
+
+/// Documented log message turned into a matcher: literal text with
+/// placeholders (`%s`, `%d`, `{}`, `#{x}`, `${x}`...) that match any text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Part {
+    Literal(String),
+    Placeholder,
+}
+
+const PRINTF_FLAGS: &str = "-+#0";
+const PRINTF_WIDTH: &str = "0123456789.*";
+const PRINTF_LENGTH: &str = "hlLqjzt";
+const PRINTF_VERBS: &str = "diouxXeEfFgGaAcspqvTtwb";
+const REGEX_SPECIAL: &str = "\\.+*?()|[]{}^$";
+
+impl From<&str> for Pattern {
+    fn from(value: &str) -> Self {
+        let chars: Vec<char> = value.chars().collect();
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            let placeholder_len = match (c, next) {
+                ('%', Some('%')) | ('{', Some('{')) | ('}', Some('}')) => {
+                    literal.push(c);
+                    i += 2;
+                    continue;
+                }
+                ('%', _) => printf_len(&chars[i..]),
+                ('{', _) => brace_len(&chars[i..]),
+                ('#', Some('{')) | ('$', Some('{')) => brace_len(&chars[i + 1..]).map(|l| l + 1),
+                _ => None,
+            };
+            match placeholder_len {
+                Some(len) => {
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    if parts.last() != Some(&Part::Placeholder) {
+                        parts.push(Part::Placeholder);
+                    }
+                    i += len;
+                }
+                None => {
+                    literal.push(c);
+                    i += 1;
+                }
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Pattern { parts }
+    }
+}
+
+impl From<&String> for Pattern {
+    fn from(value: &String) -> Self {
+        Pattern::from(value.as_str())
+    }
+}
+
+fn printf_len(chars: &[char]) -> Option<usize> {
+    let mut i = 1;
+    while i < chars.len() && PRINTF_FLAGS.contains(chars[i]) {
+        i += 1;
+    }
+    while i < chars.len() && PRINTF_WIDTH.contains(chars[i]) {
+        i += 1;
+    }
+    while i < chars.len() && PRINTF_LENGTH.contains(chars[i]) {
+        i += 1;
+    }
+    match chars.get(i) {
+        Some(verb) if PRINTF_VERBS.contains(*verb) => Some(i + 1),
+        _ => None,
+    }
+}
+
+fn brace_len(chars: &[char]) -> Option<usize> {
+    chars
+        .iter()
+        .take(64)
+        .position(|c| *c == '}')
+        .filter(|end| !chars[1..*end].contains(&'{'))
+        .map(|end| end + 1)
+}
+
+impl Pattern {
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    /// Whole `text` is an instance of the pattern.
+    pub fn is_match<T: AsRef<str>>(&self, text: T) -> bool {
+        match_parts(&self.parts, &normalize(text.as_ref()))
+    }
+
+    /// Some part of `text` is an instance of the pattern.
+    pub fn is_contained_in<T: AsRef<str>>(&self, text: T) -> bool {
+        contains_in_order(&self.parts, &normalize(text.as_ref()))
+    }
+
+    /// Amount of literal text, used to prefer the most precise pattern when
+    /// several patterns match the same line.
+    pub fn specificity(&self) -> usize {
+        self.parts
+            .iter()
+            .map(|p| match p {
+                Part::Literal(l) => l.chars().count(),
+                Part::Placeholder => 0,
+            })
+            .sum()
+    }
+
+    /// Lowercase words of the literal text, placeholders skipped.
+    pub fn words(&self) -> Vec<String> {
+        self.parts
+            .iter()
+            .filter_map(|p| match p {
+                Part::Literal(l) => Some(l),
+                Part::Placeholder => None,
+            })
+            .flat_map(|l| l.split(|c: char| !c.is_ascii_alphanumeric()))
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_lowercase())
+            .collect()
+    }
+
+    /// RE2 regular expression (as used by Prometheus and Loki) matching
+    /// instances of the pattern.
+    pub fn to_regex(&self) -> String {
+        let mut re = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(l) => {
+                    for c in l.chars() {
+                        if REGEX_SPECIAL.contains(c) {
+                            re.push('\\');
+                        }
+                        re.push(c);
+                    }
+                }
+                Part::Placeholder => re.push_str(".*"),
+            }
+        }
+        re
+    }
+
+    /// Pattern has no literal text and would match anything.
+    pub fn is_empty(&self) -> bool {
+        self.specificity() == 0
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for part in &self.parts {
+            match part {
+                Part::Literal(l) => write!(f, "{}", l)?,
+                Part::Placeholder => write!(f, "*")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.trim().to_lowercase()
+}
+
+/// Whole `text` matches: the first and last literals are anchored at the
+/// ends, the ones in between are taken at their leftmost occurrence. As
+/// placeholders match any text, the leftmost choice never has to be undone.
+fn match_parts(parts: &[Part], text: &str) -> bool {
+    let mut parts = parts;
+    let mut text = text;
+    if let Some((Part::Literal(l), rest)) = parts.split_first() {
+        match text.strip_prefix(&normalize_literal(l)) {
+            Some(tail) => text = tail,
+            None => return false,
+        }
+        parts = rest;
+    }
+    if let Some((Part::Literal(l), rest)) = parts.split_last() {
+        match text.strip_suffix(&normalize_literal(l)) {
+            Some(head) => text = head,
+            None => return false,
+        }
+        parts = rest;
+    }
+    match parts {
+        [] => text.is_empty(),
+        _ => contains_in_order(parts, text),
+    }
+}
+
+/// Literals of `parts` occur in `text` in order without overlapping.
+fn contains_in_order(parts: &[Part], text: &str) -> bool {
+    let mut text = text;
+    for part in parts {
+        if let Part::Literal(l) = part {
+            let l = normalize_literal(l);
+            match text.find(&l) {
+                Some(start) => text = &text[start + l.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+fn normalize_literal(literal: &str) -> String {
+    literal.to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_placeholders() {
+        let p = Pattern::from("user %s logged in %d times, 100%% {} #{name} ${id} {{}}");
+        assert_eq!(p.to_string(), "user * logged in * times, 100% * * * {}");
+        let p = Pattern::from("connection lost: {:?}");
+        assert_eq!(p.to_string(), "connection lost: *");
+        let p = Pattern::from("rate is 50% or more");
+        assert_eq!(p.to_string(), "rate is 50% or more");
+    }
+
+    #[test]
+    fn match_text() {
+        let p = Pattern::from("connection to %s lost after %d retries");
+        assert!(p.is_match("connection to db-1 lost after 3 retries"));
+        assert!(p.is_match("Connection to db-1 lost after 3 retries\n"));
+        assert!(!p.is_match("connection to db-1 lost"));
+        assert!(p.is_contained_in("2024-01-01 WARN connection to db-1 lost after 3 retries"));
+        assert!(!p.is_contained_in("2024-01-01 WARN connection closed"));
+        assert_eq!(p.specificity(), "connection to  lost after  retries".len());
+    }
+
+    #[test]
+    fn match_many_placeholders() {
+        let p = Pattern::from("a %s a %s a %s a %s a %s a %s a %s a %s b");
+        let text = "a ".repeat(2000);
+        assert!(!p.is_match(&text));
+        assert!(!p.is_contained_in(&text));
+        assert!(p.is_match(text.clone() + "b"));
+        let p = Pattern::from("%s-%s");
+        assert!(p.is_match("-"));
+        let p = Pattern::from("ab%sba");
+        assert!(!p.is_match("aba"));
+        assert!(p.is_match("abba"));
+    }
+
+    #[test]
+    fn regex() {
+        let p = Pattern::from("request (%s) failed: {}");
+        assert_eq!(p.to_regex(), "request \\(.*\\) failed: .*");
+    }
+}
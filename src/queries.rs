@@ -1,4 +1,4 @@
-pub const QUERY: &'static str = r#"(
+pub const QUERY: &str = r#"(
     (comment) @comment1
     (comment) @comment2
 	(expression_statement
@@ -14,7 +14,7 @@ pub const QUERY: &'static str = r#"(
     ) 
 )"#;
 
-pub const QUERY2: &'static str = r#"(
+pub const QUERY2: &str = r#"(
     (comment) @comment1 
     (comment)* @comment2
 	(expression_statement
@@ -28,7 +28,7 @@ pub const QUERY2: &'static str = r#"(
          )
     )*
 )"#;
-pub const QUERY3: &'static str = r#"(
+pub const QUERY3: &str = r#"(
     ((comment) @desc
     .
     (comment)? @action)
@@ -45,7 +45,7 @@ pub const QUERY3: &'static str = r#"(
     )
 )"#;
 
-pub const QUERY4: &'static str = r#"
+pub const QUERY4: &str = r#"
 (source_file (package_clause (package_identifier) @package))
 (
     ((comment) @comment1  
@@ -69,7 +69,7 @@ pub const QUERY4: &'static str = r#"
 )
 "#;
 
-pub const QUERY5: &'static str = r#"
+pub const QUERY5: &str = r#"
 (source_file (package_clause (package_identifier) @package))
 (
     ([((comment) @comment1  
@@ -96,7 +96,7 @@ pub const QUERY5: &'static str = r#"
 )
 "#;
 
-pub const QUERY_LOG1: &'static str = r#"
+pub const QUERY_LOG1: &str = r#"
 (
 	(
     	(
@@ -110,7 +110,7 @@ pub const QUERY_LOG1: &'static str = r#"
 )
 "#;
 
-pub const QUERY_LOG2: &'static str = r#"
+pub const QUERY_LOG2: &str = r#"
 (
 	(
     	(
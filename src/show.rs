@@ -0,0 +1,175 @@
+use std::{error::Error, io::IsTerminal};
+
+use crate::{
+    args::{self, Color, ShowArg},
+    meta::{Level, Meta},
+};
+
+const INDENT: usize = 7;
+
+pub fn run(metas: &[Meta], arg: &args::Arg, show: &ShowArg) -> Result<(), Box<dyn Error>> {
+    let stdout = std::io::stdout();
+    let color = match show.color {
+        Color::Always => true,
+        Color::Never => false,
+        Color::Auto => stdout.is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+    };
+    let width = show
+        .width
+        .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+        .unwrap_or(80);
+    let mut metas = filter(metas, show);
+    if let Some(order) = &arg.sort_by {
+        Meta::sort_by(&mut metas, order);
+    }
+    print!("{}", render(&metas, width, color));
+    Ok(())
+}
+
+/// Entries of the `--level`s containing `--grep`, ignoring case.
+fn filter(metas: &[Meta], show: &ShowArg) -> Vec<Meta> {
+    let levels: Vec<Level> = show.level.iter().map(Level::from).collect();
+    let grep = show.grep.as_ref().map(|grep| grep.to_lowercase());
+    metas
+        .iter()
+        .filter(|m| levels.is_empty() || levels.contains(&m.level))
+        .filter(|m| match &grep {
+            Some(grep) => [&m.message.0, &m.subject.0, &m.description.0]
+                .iter()
+                .any(|text| text.to_lowercase().contains(grep)),
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Entries with the level and message on the first line, subject,
+/// description, fields and locations below, wrapped to `width`.
+pub fn render(metas: &[Meta], width: usize, color: bool) -> String {
+    let paint = |code: &str, text: &str| match color {
+        true => format!("\x1b[{code}m{text}\x1b[0m"),
+        false => text.to_owned(),
+    };
+    let width = width.max(INDENT + 20) - INDENT;
+    let mut out = String::new();
+    for meta in metas {
+        let label = format!("{:<6}", meta.level.to_string().to_uppercase());
+        out.push_str(&paint(level_color(&meta.level), &label));
+        out.push(' ');
+        let mut message = wrap(&meta.message.0, width).into_iter();
+        out.push_str(&paint("1", &message.next().unwrap_or_default()));
+        out.push('\n');
+        let mut lines: Vec<String> = message.collect();
+        lines.extend(wrap(&meta.subject.0, width));
+        for line in meta.description.0.lines() {
+            lines.extend(wrap(line, width));
+        }
+        for field in &meta.fields {
+            let field = match field.description.as_str() {
+                "" => format!("field {}", field.name),
+                description => format!("field {}: {description}", field.name),
+            };
+            lines.extend(wrap(&field, width));
+        }
+        let mut locations = vec![format!("{}:{}", meta.file, meta.line)];
+        locations.extend(
+            meta.locations
+                .iter()
+                .map(|(file, line, _)| format!("{file}:{line}")),
+        );
+        lines.extend(
+            wrap(&locations.join(" "), width)
+                .iter()
+                .map(|line| paint("2", line)),
+        );
+        for line in lines {
+            out.push_str(&format!("{:INDENT$}{line}\n", ""));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn level_color(level: &Level) -> &'static str {
+    match level {
+        Level::Fatal => "1;31",
+        Level::Warn => "1;33",
+        Level::Info => "1;32",
+        Level::Debug => "1;34",
+        Level::Trace => "2",
+    }
+}
+
+/// Lines of at most `width` characters broken at whitespace; longer words
+/// get a line of their own.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let len = line.chars().count();
+        if len > 0 && len + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        args::{self, ShowArg},
+        meta::{Description, Level, Message, Meta, Subject},
+    };
+
+    use super::{filter, render, wrap};
+
+    fn meta(level: Level, message: &str, description: &str) -> Meta {
+        Meta {
+            level,
+            message: Message(message.to_owned()),
+            subject: Subject("db".to_owned()),
+            description: Description(description.to_owned()),
+            file: "main.go".to_owned(),
+            line: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn show() {
+        assert_eq!(
+            wrap("the  quick brown fox jumps", 10),
+            ["the quick", "brown fox", "jumps"]
+        );
+        let metas = [
+            meta(
+                Level::Warn,
+                "retry",
+                "the pool is exhausted\nraise max_conns",
+            ),
+            meta(Level::Fatal, "no config", "create config.toml"),
+        ];
+        let show = ShowArg {
+            level: vec![args::Level::Warn, args::Level::Fatal],
+            grep: Some("POOL".to_owned()),
+            color: args::Color::Never,
+            width: None,
+        };
+        let shown = filter(&metas, &show);
+        assert_eq!(shown.len(), 1);
+        assert_eq!(
+            render(&shown, 34, false),
+            "WARN   retry\n       db\n       the pool is exhausted\n       raise max_conns\n       main.go:3\n\n"
+        );
+        assert!(
+            render(&shown, 80, true).starts_with("\x1b[1;33mWARN  \x1b[0m \x1b[1mretry\x1b[0m\n")
+        );
+    }
+}
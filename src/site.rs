@@ -0,0 +1,250 @@
+use std::{error::Error, path::Path};
+
+use serde_json::json;
+
+use crate::{
+    args::{self, SaveType, Site},
+    meta::Level,
+    template::{render, TemplateData},
+};
+
+impl Site {
+    /// Format of the pages.
+    pub fn save_type(&self) -> SaveType {
+        match self {
+            Site::MdBook | Site::MkDocs => SaveType::MD,
+            Site::Docusaurus => SaveType::MDX,
+        }
+    }
+}
+
+/// Page of one level.
+struct Page {
+    level: Level,
+    title: String,
+    /// Path inside the docs or book source
+    path: String,
+}
+
+/// Pages of the levels with front-matter plus the navigation of the site:
+/// `SUMMARY.md` for mdBook, `sidebar.json` for Docusaurus and
+/// `mkdocs-nav.yml` for MkDocs.
+pub fn documents(
+    arg: &args::Arg,
+    site: &Site,
+    levels: Vec<(Level, TemplateData)>,
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    if *site == Site::MdBook && !arg.front_matter.is_empty() {
+        return Err("mdBook pages have no front-matter".into());
+    }
+    let save_type = site.save_type();
+    let dir = Path::new(&arg.save_path);
+    let prefix = match arg.site_prefix.trim_end_matches('/') {
+        "" => String::new(),
+        prefix => format!("{prefix}/"),
+    };
+    let mut outputs = vec![];
+    let mut pages = vec![];
+    for (position, (level, data)) in levels.into_iter().enumerate() {
+        let file = format!("{level}.{}", file_suffix(&save_type));
+        let fields = front_matter(arg, site, &level, position + 1);
+        let title = fields
+            .iter()
+            .find(|(key, _)| key == "title")
+            .and_then(|(_, value)| value.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| default_title(arg.project_name(), &level));
+        let mut page = String::new();
+        if !fields.is_empty() {
+            page.push_str("---\n");
+            for (key, value) in &fields {
+                page.push_str(&format!("{key}: {value}\n"));
+            }
+            page.push_str("---\n\n");
+        }
+        page.push_str(&render(data, &save_type)?);
+        outputs.push((dir.join(&file).display().to_string(), page));
+        pages.push(Page {
+            level,
+            title,
+            path: format!("{prefix}{file}"),
+        });
+    }
+    let label = format!("{} logs", arg.project_name());
+    let (name, navigation) = match site {
+        Site::MdBook => ("SUMMARY.md", summary(&pages)),
+        Site::Docusaurus => ("sidebar.json", sidebar(&label, &pages)?),
+        Site::MkDocs => ("mkdocs-nav.yml", nav(&label, &pages)),
+    };
+    outputs.push((dir.join(name).display().to_string(), navigation));
+    Ok(outputs)
+}
+
+fn file_suffix(save_type: &SaveType) -> &'static str {
+    match save_type {
+        SaveType::MDX => "mdx",
+        _ => "md",
+    }
+}
+
+fn default_title(project: &str, level: &Level) -> String {
+    format!("{project} - {level} logs")
+}
+
+/// Front-matter of the page of `level`: a title, the sidebar position on
+/// Docusaurus, then `--front-matter` fields replacing them. `tags` are
+/// comma-separated, numbers stay numbers.
+fn front_matter(
+    arg: &args::Arg,
+    site: &Site,
+    level: &Level,
+    position: usize,
+) -> Vec<(String, serde_json::Value)> {
+    let mut fields = vec![];
+    if *site == Site::MdBook {
+        return fields;
+    }
+    fields.push((
+        "title".to_owned(),
+        json!(default_title(arg.project_name(), level)),
+    ));
+    if *site == Site::Docusaurus {
+        fields.push(("sidebar_position".to_owned(), json!(position)));
+    }
+    for (key, value) in &arg.front_matter {
+        let value = value
+            .replace("{project}", arg.project_name())
+            .replace("{level}", &level.to_string());
+        let value = if key == "tags" {
+            json!(value
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .collect::<Vec<_>>())
+        } else if let Ok(number) = value.parse::<i64>() {
+            json!(number)
+        } else {
+            json!(value)
+        };
+        match fields.iter_mut().find(|(k, _)| k == key) {
+            Some(field) => field.1 = value,
+            None => fields.push((key.clone(), value)),
+        }
+    }
+    fields
+}
+
+/// `SUMMARY.md` fragment with a chapter per level.
+fn summary(pages: &[Page]) -> String {
+    pages
+        .iter()
+        .map(|page| format!("- [{}]({})\n", page.title, page.path))
+        .collect()
+}
+
+/// Docusaurus sidebar category; doc IDs are the paths without extension.
+fn sidebar(label: &str, pages: &[Page]) -> Result<String, Box<dyn Error>> {
+    let items: Vec<serde_json::Value> = pages
+        .iter()
+        .map(|page| {
+            let id = page.path.rsplit_once('.').map_or(&*page.path, |(id, _)| id);
+            json!({ "type": "doc", "id": id, "label": page.level.to_string() })
+        })
+        .collect();
+    let category = json!({ "type": "category", "label": label, "items": items });
+    Ok(serde_json::to_string_pretty(&category)? + "\n")
+}
+
+/// Snippet for the `nav` of `mkdocs.yml`.
+fn nav(label: &str, pages: &[Page]) -> String {
+    let mut nav = format!("- {}:\n", json!(label));
+    for page in pages {
+        nav.push_str(&format!(
+            "    - {}: {}\n",
+            json!(page.title),
+            json!(page.path)
+        ));
+    }
+    nav
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::{
+        args::{Arg, Site},
+        meta::Level,
+        template::TemplateData,
+    };
+
+    use super::documents;
+
+    fn levels() -> Vec<(Level, TemplateData)> {
+        [Level::Info, Level::Warn]
+            .into_iter()
+            .map(|level| (level, TemplateData::new("billing", level, &None)))
+            .collect()
+    }
+
+    #[test]
+    fn docusaurus() {
+        let arg = Arg::try_parse_from([
+            "logdoc",
+            "-p",
+            "billing",
+            "-l",
+            "golang",
+            "-s",
+            "docs/logs",
+            "--site",
+            "docusaurus",
+            "--site-prefix",
+            "logs",
+            "--front-matter",
+            "tags=logs,{level}",
+            "--front-matter",
+            "sidebar_position=10",
+        ])
+        .unwrap();
+        let outputs = documents(&arg, &Site::Docusaurus, levels()).unwrap();
+        assert_eq!(outputs[0].0, "docs/logs/info.mdx");
+        assert!(outputs[0].1.starts_with(
+            "---\ntitle: \"billing - info logs\"\nsidebar_position: 10\ntags: [\"logs\",\"info\"]\n---\n\n# billing - info logs\n"
+        ));
+        let sidebar: serde_json::Value = serde_json::from_str(&outputs[2].1).unwrap();
+        assert_eq!(outputs[2].0, "docs/logs/sidebar.json");
+        assert_eq!(sidebar["label"], "billing logs");
+        assert_eq!(sidebar["items"][1]["id"], "logs/warn");
+    }
+
+    #[test]
+    fn mdbook_and_mkdocs() {
+        let arg = Arg::try_parse_from([
+            "logdoc",
+            "-p",
+            "billing",
+            "-l",
+            "golang",
+            "--site-prefix",
+            "logs/",
+        ])
+        .unwrap();
+        let outputs = documents(&arg, &Site::MdBook, levels()).unwrap();
+        assert!(outputs[0].1.starts_with("# billing - info logs\n"));
+        assert_eq!(outputs[2].0, "./SUMMARY.md");
+        assert_eq!(
+            outputs[2].1,
+            "- [billing - info logs](logs/info.md)\n- [billing - warn logs](logs/warn.md)\n"
+        );
+
+        let outputs = documents(&arg, &Site::MkDocs, levels()).unwrap();
+        assert!(outputs[1]
+            .1
+            .starts_with("---\ntitle: \"billing - warn logs\"\n---\n\n"));
+        assert_eq!(
+            outputs[2].1,
+            "- \"billing logs\":\n    - \"billing - info logs\": \"logs/info.md\"\n    - \"billing - warn logs\": \"logs/warn.md\"\n"
+        );
+    }
+}
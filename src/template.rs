@@ -1,6 +1,9 @@
 use std::{error::Error, fmt::Display};
 
-use handlebars::Handlebars;
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, JsonValue as Json, Output, RenderContext,
+    Renderable,
+};
 
 use serde::Serialize;
 
@@ -11,14 +14,83 @@ pub struct TemplateData {
     pub project: String,
     pub level: String,
     pub description: Option<String>,
-    #[serde(rename = "msg_table_header")]
-    pub msg_tbl_header: Option<String>,
-    #[serde(rename = "subj_table_header")]
-    pub subj_tbl_header: Option<String>,
-    #[serde(rename = "desc_table_header")]
-    pub desc_tbl_header: Option<String>,
+    /// Table columns in order.
+    pub columns: Vec<TemplateColumn>,
     #[serde(rename = "metas")]
     pub metas: Vec<TemplateMeta>,
+    /// Group of a document generated per group.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Entries by group, sorted by group name; `metas` is empty then.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<TemplateGroup>,
+    /// Field delimiter of CSV documents.
+    #[serde(skip)]
+    pub csv_delimiter: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TemplateColumn {
+    /// Field of `TemplateMeta` shown in the column, `location` is `file:line`
+    pub key: String,
+    pub title: String,
+    /// Relative width in percent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u8>,
+}
+
+impl TemplateColumn {
+    pub fn new(column: args::Column) -> TemplateColumn {
+        TemplateColumn {
+            key: column.to_string(),
+            title: column.to_string(),
+            width: None,
+        }
+    }
+}
+
+/// Columns of `--columns` with titles and widths from the options. Titles
+/// given on the command line win over the translated `headers`.
+pub fn columns(arg: &args::Arg, headers: &crate::i18n::Headers) -> Vec<TemplateColumn> {
+    let table_headers = [
+        (args::Column::Message, &arg.message_table_header),
+        (args::Column::Subject, &arg.subject_table_header),
+        (args::Column::Description, &arg.description_table_header),
+    ];
+    arg.columns
+        .iter()
+        .map(|&column| {
+            let mut template_column = TemplateColumn::new(column);
+            let header = table_headers
+                .iter()
+                .find(|(c, _)| *c == column)
+                .and_then(|(_, header)| header.as_ref());
+            let title = arg
+                .column_title
+                .iter()
+                .rev()
+                .find(|(c, _)| *c == column)
+                .map(|(_, title)| title)
+                .or(header)
+                .or(headers.title(column));
+            if let Some(title) = title {
+                template_column.title = title.clone();
+            }
+            template_column.width = arg
+                .column_width
+                .iter()
+                .rev()
+                .find(|(c, _)| *c == column)
+                .map(|(_, width)| *width);
+            template_column
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateGroup {
+    pub name: String,
+    pub metas: Vec<TemplateMeta>,
 }
 
 impl TemplateData {
@@ -32,36 +104,64 @@ impl TemplateData {
         TemplateData {
             project,
             level,
-            msg_tbl_header: Some("message".to_owned()),
-            subj_tbl_header: Some("subject".to_owned()),
-            desc_tbl_header: Some("description".to_owned()),
+            columns: [
+                args::Column::Message,
+                args::Column::Subject,
+                args::Column::Description,
+            ]
+            .map(TemplateColumn::new)
+            .to_vec(),
             description: description.clone(),
             metas: vec![],
+            group: None,
+            groups: vec![],
+            csv_delimiter: b';',
         }
     }
     pub fn add_meta(&mut self, tm: TemplateMeta) {
         self.metas.push(tm);
     }
+    pub fn add_group_meta(&mut self, group: String, tm: TemplateMeta) {
+        match self.groups.binary_search_by(|g| g.name.cmp(&group)) {
+            Ok(i) => self.groups[i].metas.push(tm),
+            Err(i) => self.groups.insert(
+                i,
+                TemplateGroup {
+                    name: group,
+                    metas: vec![tm],
+                },
+            ),
+        }
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 pub struct TemplateMeta {
+    pub id: String,
+    pub level: String,
     pub message: String,
     pub subject: String,
     pub description: String,
+    pub file: String,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<TemplateField>,
+    /// `file:line` of every place documenting a merged entry
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateField {
+    pub name: String,
+    pub description: String,
 }
 
 impl From<Meta> for TemplateMeta {
     fn from(value: Meta) -> Self {
-        let message = value.message.format();
-        let subject = value.subject.format();
-        let description = value.description.format();
-
-        TemplateMeta {
-            message,
-            subject,
-            description,
-        }
+        TemplateMeta::from(&value)
     }
 }
 impl From<&Meta> for TemplateMeta {
@@ -69,11 +169,33 @@ impl From<&Meta> for TemplateMeta {
         let message = value.message.format();
         let subject = value.subject.format();
         let description = value.description.format();
+        let fields = value
+            .fields
+            .iter()
+            .map(|f| TemplateField {
+                name: f.name.clone(),
+                description: f.description.clone(),
+            })
+            .collect();
+        let mut locations = vec![];
+        if !value.locations.is_empty() {
+            locations.push(format!("{}:{}", value.file, value.line));
+            for (file, line, _) in &value.locations {
+                locations.push(format!("{file}:{line}"));
+            }
+        }
 
         TemplateMeta {
+            id: value.id(),
+            level: value.level.to_string(),
             message,
             subject,
             description,
+            file: value.file.clone(),
+            line: value.line,
+            module: value.module.clone(),
+            fields,
+            locations,
         }
     }
 }
@@ -82,40 +204,646 @@ pub fn render(
     templ_data: TemplateData,
     save_type: &args::SaveType,
 ) -> Result<String, Box<dyn Error>> {
-    let reg = Handlebars::new();
+    match save_type {
+        args::SaveType::JSON => return render_json(&templ_data),
+        args::SaveType::CSV => return render_csv(&templ_data),
+        args::SaveType::MAN => return Ok(render_man(std::slice::from_ref(&templ_data))),
+        _ => {}
+    }
+    let reg = registry(save_type)?;
     let templ_string = template(save_type);
     let result = reg.render_template(&templ_string, &templ_data)?;
     Ok(result)
 }
 
+/// Render without the document title, for injection into existing documents.
+pub fn render_fragment(
+    templ_data: TemplateData,
+    save_type: &args::SaveType,
+) -> Result<String, Box<dyn Error>> {
+    match save_type {
+        args::SaveType::JSON => return render_json(&templ_data),
+        args::SaveType::CSV => return render_csv(&templ_data),
+        args::SaveType::MAN => return Ok(render_man(std::slice::from_ref(&templ_data))),
+        _ => {}
+    }
+    let reg = registry(save_type)?;
+    let templ_string = fragment_template(save_type);
+    let result = reg.render_template(&templ_string, &templ_data)?;
+    Ok(result)
+}
+
+/// Entries of the level as JSON, without the table layout; descriptions
+/// keep their line breaks.
+fn render_json(templ_data: &TemplateData) -> Result<String, Box<dyn Error>> {
+    let mut data = serde_json::to_value(templ_data)?;
+    if let Some(data) = data.as_object_mut() {
+        data.remove("columns");
+    }
+    plain_descriptions(&mut data["metas"]);
+    let groups = data.get_mut("groups").and_then(Json::as_array_mut);
+    for group in groups.into_iter().flatten() {
+        plain_descriptions(&mut group["metas"]);
+    }
+    Ok(serde_json::to_string_pretty(&data)? + "\n")
+}
+
+fn plain_descriptions(metas: &mut Json) {
+    for meta in metas.as_array_mut().into_iter().flatten() {
+        if let Some(description) = meta["description"].as_str() {
+            meta["description"] = Json::String(description.replace("<br/>", "\n"));
+        }
+    }
+}
+
+/// RFC 4180 CSV: a header row with the column titles, then one record per
+/// entry. Grouped entries get a leading `group` column. Descriptions keep
+/// their line breaks.
+pub fn render_csv(templ_data: &TemplateData) -> Result<String, Box<dyn Error>> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(templ_data.csv_delimiter)
+        .terminator(csv::Terminator::CRLF)
+        .from_writer(vec![]);
+    let grouped = !templ_data.groups.is_empty();
+    let mut header: Vec<&str> = vec![];
+    if grouped {
+        header.push("group");
+    }
+    header.extend(templ_data.columns.iter().map(|c| c.title.as_str()));
+    writer.write_record(&header)?;
+    let metas = templ_data.metas.iter().map(|m| (None, m)).chain(
+        templ_data
+            .groups
+            .iter()
+            .flat_map(|g| g.metas.iter().map(move |m| (Some(g.name.as_str()), m))),
+    );
+    for (group, meta) in metas {
+        let meta = serde_json::to_value(meta)?;
+        let mut record: Vec<String> = vec![];
+        if grouped {
+            record.push(group.unwrap_or_default().to_owned());
+        }
+        for column in &templ_data.columns {
+            let value = cell(&meta, &column.key);
+            record.push(match column.key.as_str() {
+                "description" => value.replace("<br/>", "\n"),
+                _ => value,
+            });
+        }
+        writer.write_record(&record)?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Man page of section 7 with a section per level: entries are tagged
+/// paragraphs headed by the first column, the other columns, locations
+/// and fields below.
+pub fn render_man(levels: &[TemplateData]) -> String {
+    let project = levels
+        .first()
+        .map(|l| l.project.as_str())
+        .unwrap_or_default();
+    let name = man_name(project);
+    let mut page = format!(
+        ".TH \"{}\" \"7\" \"\" \"logdoc\" \"{} log messages\"\n.SH NAME\n{} \\- log messages of {}\n",
+        escape_roff(&name.to_uppercase()),
+        escape_roff(project),
+        escape_roff(&name),
+        escape_roff(project)
+    );
+    for data in levels {
+        if data.metas.is_empty() && data.groups.is_empty() {
+            continue;
+        }
+        let level = match &data.group {
+            Some(group) => format!("{group} {}", data.level),
+            None => data.level.clone(),
+        };
+        page.push_str(&format!(".SH {}\n", escape_roff(&level.to_uppercase())));
+        if let Some(description) = &data.description {
+            page.push_str(&roff_lines(description.lines()));
+        }
+        for meta in &data.metas {
+            man_entry(&mut page, meta, &data.columns);
+        }
+        for group in &data.groups {
+            page.push_str(&format!(".SS {}\n", escape_roff(&group.name)));
+            for meta in &group.metas {
+                man_entry(&mut page, meta, &data.columns);
+            }
+        }
+    }
+    page
+}
+
+/// `billing-logs` of project `Billing`.
+pub fn man_name(project: &str) -> String {
+    let name: String = project
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("{}-logs", name.trim_matches('-'))
+}
+
+fn man_entry(page: &mut String, meta: &TemplateMeta, columns: &[TemplateColumn]) {
+    let Ok(meta_json) = serde_json::to_value(meta) else {
+        return;
+    };
+    let mut columns = columns.iter();
+    let tag = columns
+        .next()
+        .map(|column| cell(&meta_json, &column.key))
+        .unwrap_or_default();
+    page.push_str(&format!(".TP\n\\fB{}\\fR\n", escape_roff(&tag)));
+    let mut lines: Vec<String> = vec![];
+    for column in columns {
+        let value = cell(&meta_json, &column.key);
+        lines.extend(
+            value
+                .split("<br/>")
+                .filter(|line| !line.is_empty())
+                .map(String::from),
+        );
+    }
+    if !meta.locations.is_empty() {
+        lines.push(format!("found in {}", meta.locations.join(" ")));
+    }
+    for field in &meta.fields {
+        match field.description.as_str() {
+            "" => lines.push(format!("field {}", field.name)),
+            description => lines.push(format!("field {}: {description}", field.name)),
+        }
+    }
+    page.push_str(&roff_lines(lines.iter().map(String::as_str)));
+}
+
+/// Text lines separated by `.br` requests.
+fn roff_lines<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    let lines: Vec<String> = lines.map(|line| escape_roff(line) + "\n").collect();
+    lines.join(".br\n")
+}
+
+/// roff escaping of backslashes, hyphens and control characters at the
+/// start of a line.
+fn escape_roff(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        return format!("\\&{escaped}");
+    }
+    escaped
+}
+
+/// Value of column `key` of a serialized `TemplateMeta`.
+fn cell(meta: &Json, key: &str) -> String {
+    let text = |key: &str| match &meta[key] {
+        Json::String(s) => s.clone(),
+        Json::Null => String::new(),
+        other => other.to_string(),
+    };
+    match key {
+        "location" => format!("{}:{}", text("file"), text("line")),
+        _ => text(key),
+    }
+}
+
+/// Handlebars with the `table` partial of the save type, its escaping and
+/// the `cell`, `dashes`, `widths` and `underline` helpers.
+fn registry(save_type: &args::SaveType) -> Result<Handlebars<'static>, Box<dyn Error>> {
+    let mut reg = Handlebars::new();
+    reg.register_partial("table", table_template(save_type))?;
+    let lines = match save_type {
+        args::SaveType::MD => {
+            reg.register_escape_fn(escape_md);
+            Some(("", "<br/>"))
+        }
+        args::SaveType::MDX => {
+            reg.register_escape_fn(escape_mdx);
+            Some(("", "<br/>"))
+        }
+        args::SaveType::ADOC => {
+            reg.register_escape_fn(escape_adoc);
+            Some(("", " +\n"))
+        }
+        args::SaveType::RST => {
+            reg.register_escape_fn(escape_rst);
+            Some(("| ", "\n       | "))
+        }
+        _ => Some(("", "<br/>")),
+    };
+    reg.register_helper("cell", Box::new(CellHelper { lines }));
+    reg.register_helper("dashes", Box::new(dashes_helper));
+    reg.register_helper("widths", Box::new(widths_helper));
+    reg.register_helper("underline", Box::new(UnderlineHelper));
+    Ok(reg)
+}
+
+/// Markdown escaping: HTML plus table cell separators.
+fn escape_md(text: &str) -> String {
+    handlebars::html_escape(text).replace('|', "\\|")
+}
+
+/// MDX escaping: HTML plus the braces of JSX expressions and table cell
+/// separators.
+fn escape_mdx(text: &str) -> String {
+    handlebars::html_escape(text)
+        .replace('{', "&#123;")
+        .replace('}', "&#125;")
+        .replace('|', "&#124;")
+}
+
+/// AsciiDoc escaping: cell separators, attribute references and inline
+/// markup. Markup characters become character references, backslashes
+/// don't escape constrained formatting reliably.
+fn escape_adoc(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '|' => escaped.push_str("\\|"),
+            '*' | '_' | '`' | '#' | '+' | '^' | '~' => {
+                escaped.push_str(&format!("&#{};", c as u32))
+            }
+            '{' if chars
+                .peek()
+                .is_some_and(|c| c.is_alphanumeric() || *c == '_') =>
+            {
+                escaped.push_str("\\{")
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// reStructuredText escaping of inline markup characters.
+fn escape_rst(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\*`|_".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// `{{cell meta column.key}}`: value of a column of an entry, escaped.
+/// Description lines are split at `<br/>` and joined as `lines`
+/// (line prefix, separator); without `lines` the description is written
+/// as is for Markdown and HTML.
+struct CellHelper {
+    lines: Option<(&'static str, &'static str)>,
+}
+
+impl HelperDef for CellHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let meta = h.param(0).map(|p| p.value()).unwrap_or(&Json::Null);
+        let key = h
+            .param(1)
+            .and_then(|p| p.value().as_str())
+            .unwrap_or_default();
+        let value = cell(meta, key);
+        match (key, self.lines) {
+            ("description", None) => out.write(&value)?,
+            ("description", Some(_)) if value.is_empty() => {}
+            ("description", Some((prefix, separator))) => {
+                let lines: Vec<String> = value
+                    .split("<br/>")
+                    .map(|line| r.get_escape_fn()(line))
+                    .collect();
+                out.write(prefix)?;
+                out.write(&lines.join(separator))?;
+            }
+            _ => out.write(&r.get_escape_fn()(&value))?,
+        }
+        Ok(())
+    }
+}
+
+/// `{{dashes column.width}}`: Markdown separator of a column, as many
+/// dashes as the width so relative widths carry over to Pandoc.
+fn dashes_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let width = h.param(0).and_then(|p| p.value().as_u64()).unwrap_or(3);
+    out.write(&"-".repeat(width.max(3) as usize))?;
+    Ok(())
+}
+
+/// `{{widths @root.columns ","}}`: relative widths of the columns, the
+/// ones without a width share the rest equally.
+fn widths_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let columns = h
+        .param(0)
+        .and_then(|p| p.value().as_array())
+        .cloned()
+        .unwrap_or_default();
+    let separator = h.param(1).and_then(|p| p.value().as_str()).unwrap_or(",");
+    let widths: Vec<Option<u64>> = columns.iter().map(|c| c["width"].as_u64()).collect();
+    let set: u64 = widths.iter().flatten().sum();
+    let unset = widths.iter().filter(|w| w.is_none()).count() as u64;
+    let share = match unset {
+        0 => 0,
+        unset => (100u64.saturating_sub(set) / unset).max(1),
+    };
+    let widths: Vec<String> = widths
+        .iter()
+        .map(|w| w.unwrap_or(share).to_string())
+        .collect();
+    out.write(&widths.join(separator))?;
+    Ok(())
+}
+
+/// `{{#underline "="}}title{{/underline}}`: reStructuredText section title
+/// underlined with the character.
+struct UnderlineHelper;
+
+impl HelperDef for UnderlineHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let character = h.param(0).and_then(|p| p.value().as_str()).unwrap_or("=");
+        let title = match h.template() {
+            Some(template) => template.renders(r, ctx, rc)?,
+            None => String::new(),
+        };
+        out.write(&title)?;
+        out.write("\n")?;
+        out.write(&character.repeat(title.chars().count()))?;
+        Ok(())
+    }
+}
+
 fn template(save_type: &args::SaveType) -> String {
+    format!(
+        "{}{}{}",
+        title_template(save_type),
+        fragment_template(save_type),
+        footer_template(save_type)
+    )
+}
+
+fn title_template(save_type: &args::SaveType) -> &'static str {
+    match save_type {
+        args::SaveType::MD | args::SaveType::MDX => {
+            "# {{ project }} - {{#if group}}{{ group }} - {{/if}}{{ level }} logs\n\n"
+        }
+        args::SaveType::ADOC => {
+            "= {{ project }} - {{#if group}}{{ group }} - {{/if}}{{ level }} logs\n\n"
+        }
+        args::SaveType::RST => {
+            "{{#underline \"=\"}}{{ project }} - {{#if group}}{{ group }} - {{/if}}{{ level }} logs{{/underline}}\n\n"
+        }
+        args::SaveType::CSV | args::SaveType::JSON | args::SaveType::MAN => "",
+        args::SaveType::HTML => {
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{{ project }} - {{#if group}}{{ group }} - {{/if}}{{ level }} logs</title>
+</head>
+<body>
+<h1>{{ project }} - {{#if group}}{{ group }} - {{/if}}{{ level }} logs</h1>
+"#
+        }
+    }
+}
+
+fn footer_template(save_type: &args::SaveType) -> &'static str {
+    match save_type {
+        args::SaveType::HTML => "</body>\n</html>\n",
+        args::SaveType::MD
+        | args::SaveType::MDX
+        | args::SaveType::ADOC
+        | args::SaveType::RST
+        | args::SaveType::CSV
+        | args::SaveType::JSON
+        | args::SaveType::MAN => "",
+    }
+}
+
+fn fragment_template(save_type: &args::SaveType) -> String {
     match save_type {
-        args::SaveType::MD => String::from(
-            r#"# {{ project }} - {{ level }} logs
+        args::SaveType::MD | args::SaveType::MDX => String::from(
+            r#"{{#if description}}
+{{ description }}
+
+{{/if}}
+{{#if groups}}
+{{#each groups}}
+## {{name}}
+
+{{> table}}
+
+{{/each}}
+{{else}}
+{{> table}}
+{{/if}}"#,
+        ),
+        args::SaveType::ADOC => String::from(
+            r#"{{#if description}}
+{{ description }}
+
+{{/if}}
+{{#if groups}}
+{{#each groups}}
+== {{name}}
+
+{{> table}}
 
+{{/each}}
+{{else}}
+{{> table}}
+{{/if}}"#,
+        ),
+        args::SaveType::RST => String::from(
+            r#"{{#if description}}
 {{ description }}
 
-|{{msg_table_header}}|{{subj_table_header}}|{{desc_table_header}}|
-|---|---|---|
+{{/if}}
+{{#if groups}}
+{{#each groups}}
+{{#underline "-"}}{{name}}{{/underline}}
+
+{{> table}}
+
+{{/each}}
+{{else}}
+{{> table}}
+{{/if}}"#,
+        ),
+        args::SaveType::HTML => String::from(
+            r#"{{#if description}}
+<p>{{ description }}</p>
+{{/if}}
+{{#if groups}}
+{{#each groups}}
+<h2>{{name}}</h2>
+{{> table}}
+{{/each}}
+{{else}}
+{{> table}}
+{{/if}}
+"#,
+        ),
+        args::SaveType::CSV | args::SaveType::JSON | args::SaveType::MAN => String::new(),
+    }
+}
+
+/// Table of the `metas` of the current context: the document or a group.
+fn table_template(save_type: &args::SaveType) -> String {
+    match save_type {
+        args::SaveType::MD | args::SaveType::MDX => String::from(
+            r#"|{{#each @root.columns as |column|}}{{column.title}}|{{/each}}
+|{{#each @root.columns as |column|}}{{dashes column.width}}|{{/each}}
+{{#each metas as |meta|}}
+|{{#each @root.columns as |column|}}{{#unless (eq column.key "description")}} {{/unless}}{{cell meta column.key}}{{#if (eq column.key "description")}}{{#if meta.locations}}<br/>found in{{#each meta.locations}} `{{this}}`{{/each}}{{/if}}{{/if}} |{{/each}}
+{{/each}}
 {{#each metas as |meta|}}
-| {{meta.message}} | {{meta.subject}} |{{{meta.description}}} |
+{{#if meta.fields}}
+
+#### Fields of `{{meta.message}}`
+
+|field|description|
+|---|---|
+{{#each meta.fields as |field|}}
+| {{field.name}} | {{field.description}} |
+{{/each}}
+{{/if}}
 {{/each}}"#,
         ),
-        args::SaveType::CSV => String::from(
-            r#"{{#each metas as |meta|}}
-{{meta.message}};{{meta.subject}};{{meta.description}}
+        args::SaveType::ADOC => String::from(
+            r#"[%header,cols="{{widths @root.columns ","}}"]
+|===
+{{#each @root.columns as |column|}}
+|{{column.title}}
+{{/each}}
+{{#each metas as |meta|}}
+
+{{#each @root.columns as |column|}}
+|{{cell meta column.key}}{{#if (eq column.key "description")}}{{#if meta.locations}}{{#if meta.description}} +{{/if}}
+found in{{#each meta.locations}} `+{{{this}}}+`{{/each}}{{/if}}{{/if}}
+{{/each}}
+{{/each}}
+|===
+{{#each metas as |meta|}}
+{{#if meta.fields}}
+
+[discrete]
+==== Fields of `+{{{meta.message}}}+`
+
+[%header,cols="1,2"]
+|===
+|field
+|description
+{{#each meta.fields as |field|}}
+
+|`+{{{field.name}}}+`
+|{{field.description}}
+{{/each}}
+|===
+{{/if}}
 {{/each}}"#,
         ),
+        args::SaveType::RST => String::from(
+            r#"{{#if metas}}
+.. list-table::
+   :header-rows: 1
+   :widths: {{widths @root.columns " "}}
+
+{{#each @root.columns as |column|}}
+   {{#if @first}}*{{else}} {{/if}} - {{column.title}}
+{{/each}}
+{{#each metas as |meta|}}
+{{#each @root.columns as |column|}}
+   {{#if @first}}*{{else}} {{/if}} - {{cell meta column.key}}{{#if (eq column.key "description")}}{{#if meta.locations}}
+       | found in{{#each meta.locations}} ``{{{this}}}``{{/each}}{{/if}}{{/if}}
+{{/each}}
+{{/each}}
+{{/if}}
+{{#each metas as |meta|}}
+{{#if meta.fields}}
+
+.. rubric:: Fields of {{meta.message}}
+
+.. list-table::
+   :header-rows: 1
+   :widths: 1 2
+
+   * - field
+     - description
+{{#each meta.fields as |field|}}
+   * - ``{{{field.name}}}``
+     - {{field.description}}
+{{/each}}
+{{/if}}
+{{/each}}"#,
+        ),
+        args::SaveType::HTML => String::from(
+            r#"<table>
+<thead>
+<tr>{{#each @root.columns as |column|}}<th{{#if column.width}} style="width: {{column.width}}%"{{/if}}>{{column.title}}</th>{{/each}}</tr>
+</thead>
+<tbody>
+{{#each metas as |meta|}}
+<tr>
+{{#each @root.columns as |column|}}
+<td>{{cell meta column.key}}{{#if (eq column.key "description")}}{{#if meta.locations}}<br/>found in{{#each meta.locations}} <code>{{this}}</code>{{/each}}{{/if}}{{/if}}</td>
+{{/each}}
+</tr>
+{{/each}}
+</tbody>
+</table>
+{{#each metas as |meta|}}
+{{#if meta.fields}}
+<h4>Fields of <code>{{meta.message}}</code></h4>
+<table>
+<tr><th>field</th><th>description</th></tr>
+{{#each meta.fields as |field|}}
+<tr><td><code>{{field.name}}</code></td><td>{{field.description}}</td></tr>
+{{/each}}
+</table>
+{{/if}}
+{{/each}}
+"#,
+        ),
+        args::SaveType::CSV | args::SaveType::JSON | args::SaveType::MAN => String::new(),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::args;
+    use crate::i18n::Headers;
     use crate::meta::Level;
 
+    use clap::Parser;
+
     use super::render;
-    use super::{TemplateData, TemplateMeta};
+    use super::{columns, render_man, TemplateColumn, TemplateData, TemplateField, TemplateMeta};
 
     #[test]
     fn render_test() {
@@ -125,12 +853,18 @@ mod tests {
             message: "test message".to_string(),
             subject: "do nothing".to_string(),
             description: "".to_string(),
+            fields: vec![],
+            locations: vec![],
+            ..Default::default()
         };
         #[allow(unused_variables)]
         let tm2 = TemplateMeta {
             message: "test message".to_string(),
             subject: "do nothing".to_string(),
             description: "".to_string(),
+            fields: vec![],
+            locations: vec![],
+            ..Default::default()
         };
 
         let mut td = TemplateData::new(
@@ -142,8 +876,291 @@ mod tests {
             message: "msg1".to_owned(),
             subject: "subj1".to_owned(),
             description: "desc1".to_owned(),
+            fields: vec![],
+            locations: vec![],
+            ..Default::default()
         });
         let result = render(td, &st).unwrap();
         assert_eq!(result.len(), 114);
     }
+
+    #[test]
+    fn render_locations() {
+        let mut td = TemplateData::new("project", Level::Info, &None);
+        td.add_meta(TemplateMeta {
+            message: "started".to_owned(),
+            subject: "server".to_owned(),
+            description: "".to_owned(),
+            fields: vec![],
+            locations: vec!["main.go:1".to_owned(), "main.go:5".to_owned()],
+            ..Default::default()
+        });
+        let result = render(td, &args::SaveType::MD).unwrap();
+        assert!(result.contains("| started | server |<br/>found in `main.go:1` `main.go:5` |\n"));
+    }
+
+    #[test]
+    fn render_fields() {
+        let mut td = TemplateData::new("project", Level::Warn, &None);
+        td.add_meta(TemplateMeta {
+            message: "login failed".to_owned(),
+            subject: "wrong password".to_owned(),
+            description: "".to_owned(),
+            fields: vec![TemplateField {
+                name: "user".to_owned(),
+                description: "the account id".to_owned(),
+            }],
+            locations: vec![],
+            ..Default::default()
+        });
+        let result = render(td, &args::SaveType::MD).unwrap();
+        assert!(result.contains(
+            "#### Fields of `login failed`\n\n|field|description|\n|---|---|\n| user | the account id |\n"
+        ));
+
+        let mut td = TemplateData::new("project", Level::Warn, &None);
+        td.add_meta(TemplateMeta {
+            message: "login failed".to_owned(),
+            subject: "wrong password".to_owned(),
+            description: "".to_owned(),
+            fields: vec![TemplateField {
+                name: "user".to_owned(),
+                description: "the account id".to_owned(),
+            }],
+            locations: vec![],
+            ..Default::default()
+        });
+        let result = render(td, &args::SaveType::JSON).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json.get("columns"), None);
+        assert_eq!(json["metas"][0]["fields"][0]["name"], "user");
+        assert_eq!(
+            json["metas"][0]["fields"][0]["description"],
+            "the account id"
+        );
+    }
+
+    #[test]
+    fn render_escaped_descriptions() {
+        let data = || {
+            let mut td = TemplateData::new("project", Level::Warn, &None);
+            td.add_meta(TemplateMeta {
+                message: "lookup failed".to_owned(),
+                subject: "no user".to_owned(),
+                description: "<id> is unknown<br/><script>x</script>".to_owned(),
+                fields: vec![TemplateField {
+                    name: "user".to_owned(),
+                    description: "the account id".to_owned(),
+                }],
+                ..Default::default()
+            });
+            td
+        };
+        let result = render(data(), &args::SaveType::MD).unwrap();
+        assert!(result.contains(
+            "| lookup failed | no user |&lt;id&gt; is unknown<br/>&lt;script&gt;x&lt;/script&gt; |\n"
+        ));
+        let mut piped = data();
+        piped.metas[0].message = "a | b".to_owned();
+        piped.metas[0].description = "either x|y".to_owned();
+        let result = render(piped, &args::SaveType::MD).unwrap();
+        assert!(result.contains("| a \\| b | no user |either x\\|y |\n"));
+        let result = render(data(), &args::SaveType::HTML).unwrap();
+        assert!(
+            result.contains("<td>&lt;id&gt; is unknown<br/>&lt;script&gt;x&lt;/script&gt;</td>")
+        );
+        assert!(result.contains(
+            "<h4>Fields of <code>lookup failed</code></h4>\n<table>\n<tr><th>field</th><th>description</th></tr>\n\
+             <tr><td><code>user</code></td><td>the account id</td></tr>\n</table>\n"
+        ));
+        let result = render(data(), &args::SaveType::JSON).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(
+            json["metas"][0]["description"],
+            "<id> is unknown\n<script>x</script>"
+        );
+        assert_eq!(json.get("groups"), None);
+    }
+
+    #[test]
+    fn render_groups() {
+        let meta = |message: &str| TemplateMeta {
+            message: message.to_owned(),
+            subject: "s".to_owned(),
+            description: "".to_owned(),
+            fields: vec![],
+            locations: vec![],
+            ..Default::default()
+        };
+        let mut td = TemplateData::new("project", Level::Info, &None);
+        td.add_group_meta("orders".to_owned(), meta("order created"));
+        td.add_group_meta("billing".to_owned(), meta("invoice sent"));
+        td.add_group_meta("orders".to_owned(), meta("order paid"));
+        let result = render(td, &args::SaveType::MD).unwrap();
+        assert_eq!(
+            result,
+            "# project - info logs\n\n## billing\n\n|message|subject|description|\n|---|---|---|\n| invoice sent | s | |\n\n## orders\n\n|message|subject|description|\n|---|---|---|\n| order created | s | |\n| order paid | s | |\n\n"
+        );
+    }
+
+    #[test]
+    fn render_columns() {
+        let arg = args::Arg::try_parse_from([
+            "logdoc",
+            "-p",
+            "project",
+            "-l",
+            "golang",
+            "--columns",
+            "id,message,location",
+            "--message_table_header",
+            "Meldung",
+            "--column-title",
+            "id=ID",
+            "--column-width",
+            "message=60",
+        ])
+        .unwrap();
+        let meta = || TemplateMeta {
+            id: "a1b2".to_owned(),
+            message: "a < b".to_owned(),
+            file: "main.go".to_owned(),
+            line: 7,
+            ..Default::default()
+        };
+
+        let mut td = TemplateData::new("project", Level::Info, &None);
+        td.columns = columns(&arg, &Headers::default());
+        td.add_meta(meta());
+        let result = render(td, &args::SaveType::MD).unwrap();
+        assert!(result.contains(
+            "|ID|Meldung|location|\n|---|------------------------------------------------------------|---|\n| a1b2 | a &lt; b | main.go:7 |\n"
+        ));
+
+        let mut td = TemplateData::new("project", Level::Info, &None);
+        td.columns = columns(&arg, &Headers::default());
+        td.add_meta(meta());
+        let result = render(td, &args::SaveType::HTML).unwrap();
+        assert!(result.contains(
+            "<tr><th>ID</th><th style=\"width: 60%\">Meldung</th><th>location</th></tr>"
+        ));
+        assert!(
+            result.contains("<tr>\n<td>a1b2</td>\n<td>a &lt; b</td>\n<td>main.go:7</td>\n</tr>\n")
+        );
+        assert!(!result.contains("<th>fields</th>"));
+
+        let mut td = TemplateData::new("project", Level::Info, &None);
+        td.columns = columns(&arg, &Headers::default());
+        td.add_meta(meta());
+        let result = render(td, &args::SaveType::CSV).unwrap();
+        assert_eq!(result, "ID;Meldung;location\r\na1b2;a < b;main.go:7\r\n");
+
+        // titles given on the command line win over a catalog's headers
+        let headers = Headers {
+            message: Some("Nachricht".to_owned()),
+            description: Some("Beschreibung".to_owned()),
+            ..Default::default()
+        };
+        let arg = args::Arg::try_parse_from([
+            "logdoc",
+            "-p",
+            "project",
+            "-l",
+            "golang",
+            "--columns",
+            "message,description",
+            "--column-title",
+            "message=Text",
+        ])
+        .unwrap();
+        let titles: Vec<String> = columns(&arg, &headers)
+            .into_iter()
+            .map(|c| c.title)
+            .collect();
+        assert_eq!(titles, ["Text", "Beschreibung"]);
+    }
+
+    #[test]
+    fn render_csv() {
+        let meta = |message: &str, description: &str| TemplateMeta {
+            level: "warn".to_owned(),
+            message: message.to_owned(),
+            subject: "db".to_owned(),
+            description: description.to_owned(),
+            file: "main.go".to_owned(),
+            line: 3,
+            ..Default::default()
+        };
+        let mut td = TemplateData::new("project", Level::Warn, &None);
+        td.csv_delimiter = b',';
+        td.columns.push(TemplateColumn::new(args::Column::Line));
+        td.add_meta(meta("retry, giving up", "first<br/>second \"try\""));
+        let result = render(td, &args::SaveType::CSV).unwrap();
+        assert_eq!(
+            result,
+            "message,subject,description,line\r\n\"retry, giving up\",db,\"first\nsecond \"\"try\"\"\",3\r\n"
+        );
+
+        let mut td = TemplateData::new("project", Level::Warn, &None);
+        td.add_group_meta("billing".to_owned(), meta("retry", ""));
+        let result = render(td, &args::SaveType::CSV).unwrap();
+        assert_eq!(
+            result,
+            "group;message;subject;description\r\nbilling;retry;db;\r\n"
+        );
+    }
+
+    #[test]
+    fn render_adoc_rst() {
+        let data = || {
+            let mut td = TemplateData::new("project", Level::Warn, &None);
+            td.add_group_meta(
+                "billing".to_owned(),
+                TemplateMeta {
+                    message: "retry *all* | {id}".to_owned(),
+                    subject: "db_pool".to_owned(),
+                    description: "first<br/>second".to_owned(),
+                    locations: vec!["main.go:4".to_owned()],
+                    ..Default::default()
+                },
+            );
+            td
+        };
+        let result = render(data(), &args::SaveType::ADOC).unwrap();
+        assert!(result.starts_with("= project - warn logs\n\n== billing\n\n"));
+        assert!(result.contains(
+            "|retry &#42;all&#42; \\| \\{id}\n|db&#95;pool\n|first +\nsecond +\nfound in `+main.go:4+`\n|===\n"
+        ));
+
+        let result = render(data(), &args::SaveType::RST).unwrap();
+        assert!(result.starts_with(
+            "project - warn logs\n===================\n\nbilling\n-------\n\n.. list-table::\n"
+        ));
+        assert!(result.contains(
+            "   * - retry \\*all\\* \\| {id}\n     - db\\_pool\n     - | first\n       | second\n       | found in ``main.go:4``\n"
+        ));
+    }
+
+    #[test]
+    fn render_man_page() {
+        let mut warn = TemplateData::new("My Service", Level::Warn, &Some(".hidden".to_owned()));
+        warn.add_meta(TemplateMeta {
+            message: "retry -all".to_owned(),
+            subject: "db".to_owned(),
+            description: "first<br/>C:\\temp".to_owned(),
+            fields: vec![TemplateField {
+                name: "user".to_owned(),
+                description: "".to_owned(),
+            }],
+            ..Default::default()
+        });
+        let info = TemplateData::new("My Service", Level::Info, &None);
+        assert_eq!(
+            render_man(&[info, warn]),
+            ".TH \"MY\\-SERVICE\\-LOGS\" \"7\" \"\" \"logdoc\" \"My Service log messages\"\n\
+             .SH NAME\nmy\\-service\\-logs \\- log messages of My Service\n\
+             .SH WARN\n\\&.hidden\n\
+             .TP\n\\fBretry \\-all\\fR\ndb\n.br\nfirst\n.br\nC:\\etemp\n.br\nfield user\n"
+        );
+    }
 }
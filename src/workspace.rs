@@ -0,0 +1,566 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::{
+    app::Application,
+    args::{self, Arg, GroupOutput, Language},
+    files,
+    meta::{Level, Meta},
+};
+
+/// Workspace config: one `[[project]]` table per project, paths relative to
+/// the config file.
+#[derive(Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Workspace {
+    /// Path of the aggregated index, `<save_path>/index.md` when omitted
+    pub index: Option<String>,
+    #[serde(default, rename = "project")]
+    pub projects: Vec<Project>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Project {
+    pub name: String,
+    pub root: String,
+    pub language: String,
+    /// Output directory, `<save_path>/<name>` when omitted
+    pub output: Option<String>,
+    /// Directories below `root` that belong to other projects
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Marker files and the language of the project they mark, checked in order.
+const MARKERS: [(&str, Language); 11] = [
+    ("go.mod", Language::Golang),
+    ("Cargo.toml", Language::Rust),
+    ("tsconfig.json", Language::TypeScript),
+    ("package.json", Language::JavaScript),
+    ("pyproject.toml", Language::Python),
+    ("setup.py", Language::Python),
+    ("pom.xml", Language::Java),
+    ("build.gradle.kts", Language::Kotlin),
+    ("build.gradle", Language::Java),
+    ("composer.json", Language::Php),
+    ("Gemfile", Language::Ruby),
+];
+
+impl Workspace {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Workspace, Box<dyn Error>> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)?;
+        let mut workspace: Workspace =
+            toml::from_str(&data).map_err(|e| format!("{}: {e}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        workspace.index = workspace.index.map(|index| relative_to(base, &index));
+        for project in workspace.projects.iter_mut() {
+            project.root = relative_to(base, &project.root);
+            project.output = project.output.as_ref().map(|o| relative_to(base, o));
+            project.exclude = project
+                .exclude
+                .iter()
+                .map(|dir| relative_to(base, dir))
+                .collect();
+        }
+        Ok(workspace)
+    }
+
+    /// Projects under `root`, one per directory holding a marker file.
+    /// Projects nested in another one are excluded from its sources; a
+    /// project with no sources of its own besides nested projects (a
+    /// workspace manifest) is dropped. Projects are named after their
+    /// directory, or after their path below `root` when directory names
+    /// repeat.
+    pub fn discover<P: AsRef<Path>>(root: P) -> Result<Workspace, Box<dyn Error>> {
+        let root = root.as_ref();
+        let mut projects = vec![];
+        discover_in(root, &mut projects)?;
+        for i in 0..projects.len() {
+            let nested: Vec<String> = projects
+                .iter()
+                .filter(|p| {
+                    p.root != projects[i].root && Path::new(&p.root).starts_with(&projects[i].root)
+                })
+                .map(|p| p.root.clone())
+                .collect();
+            projects[i].exclude = nested;
+        }
+        let mut kept = vec![];
+        for project in projects {
+            if project.exclude.is_empty()
+                || !project_files(&project, &project_language(&project)?)?.is_empty()
+            {
+                kept.push(project);
+            }
+        }
+        let mut projects = kept;
+        let names: Vec<String> = projects.iter().map(|p| p.name.clone()).collect();
+        for project in projects.iter_mut() {
+            if names.iter().filter(|name| **name == project.name).count() > 1 {
+                project.name = path_name(root, Path::new(&project.root));
+            }
+        }
+        Ok(Workspace {
+            index: None,
+            projects,
+        })
+    }
+}
+
+fn discover_in(dir: &Path, projects: &mut Vec<Project>) -> Result<(), Box<dyn Error>> {
+    let marker = MARKERS
+        .iter()
+        .find(|(marker, _)| dir.join(marker).is_file());
+    if let Some((_, language)) = marker {
+        let name = std::fs::canonicalize(dir)?
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("root")
+            .to_owned();
+        projects.push(Project {
+            name,
+            root: dir.display().to_string(),
+            language: language.to_possible_value().unwrap().get_name().to_owned(),
+            output: None,
+            exclude: vec![],
+        });
+    }
+    let mut entries = std::fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || files::SKIPPED_DIRS.contains(&&*name) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            discover_in(&entry.path(), projects)?;
+        }
+    }
+    Ok(())
+}
+
+/// Name of a project from its path below the discovery root:
+/// `services/api` becomes `services-api`.
+fn path_name(root: &Path, dir: &Path) -> String {
+    let name = dir
+        .strip_prefix(root)
+        .unwrap_or(dir)
+        .components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("-");
+    if name.is_empty() {
+        "root".to_owned()
+    } else {
+        name
+    }
+}
+
+fn relative_to(base: &Path, path: &str) -> String {
+    base.join(path).display().to_string()
+}
+
+/// Process every project of the workspace and write the aggregated index.
+pub fn run(arg: &Arg) -> Result<(), Box<dyn Error>> {
+    if arg.inject.is_some() {
+        return Err("--inject can't be combined with a workspace".into());
+    }
+    if let Some(args::Command::Explain(_)) = arg.command {
+        return Err("explain can't be combined with a workspace".into());
+    }
+    let workspace = match (&arg.workspace, &arg.discover) {
+        (Some(config), _) => Workspace::load(config)?,
+        (None, Some(root)) => Workspace::discover(root)?,
+        (None, None) => return Err("no workspace config or discovery root".into()),
+    };
+    if workspace.projects.is_empty() {
+        return Err("workspace has no projects".into());
+    }
+    for (i, project) in workspace.projects.iter().enumerate() {
+        if workspace.projects[..i]
+            .iter()
+            .any(|p| p.name == project.name)
+        {
+            return Err(format!("duplicate project name {}", project.name).into());
+        }
+    }
+    let index = workspace
+        .index
+        .clone()
+        .unwrap_or_else(|| format!("{}.md", Path::new(&arg.save_path).join("index").display()));
+
+    let mut summaries = vec![];
+    let mut stale = vec![];
+    for project in &workspace.projects {
+        let mut project_arg = project_arg(arg, project)?;
+        if !project.exclude.is_empty() {
+            project_arg.files = Some(project_files(project, &project_arg.language()?)?);
+            project_arg.directories = None;
+        }
+        let scan = Application::collect(&project_arg)?;
+        if let Err(e) = Application::execute(&project_arg, &scan) {
+            if !arg.check {
+                return Err(format!("{}: {e}", project.name).into());
+            }
+            stale.push(format!("{}: {e}", project.name));
+        }
+        summaries.push(Summary::new(project, &project_arg, &scan.metas));
+    }
+
+    let index_data = render_index(Path::new(&index), &summaries);
+    if arg.check {
+        if let Err(e) = files::check_files(&[(index, index_data)]) {
+            stale.push(e.to_string());
+        }
+        if !stale.is_empty() {
+            return Err(stale.join("\n").into());
+        }
+        return Ok(());
+    }
+    files::save(index_data, index, arg.dry_run)?;
+    Ok(())
+}
+
+/// Arguments of a single project: the shared options with the project's
+/// name, language, sources and output directory.
+fn project_arg(arg: &Arg, project: &Project) -> Result<Arg, Box<dyn Error>> {
+    let language = project_language(project)?;
+    let mut project_arg = arg.clone();
+    project_arg.project_name = Some(project.name.clone());
+    project_arg.language = Some(language);
+    project_arg.directories = Some(vec![project.root.clone()]);
+    project_arg.recurse = true;
+    project_arg.files = None;
+    project_arg.workspace = None;
+    project_arg.discover = None;
+    project_arg.save_path = match &project.output {
+        Some(output) => output.clone(),
+        None => Path::new(&arg.save_path)
+            .join(&project.name)
+            .display()
+            .to_string(),
+    };
+    // files named by the command are per project: the baseline and
+    // generated code next to the sources, reports in the output directory
+    let root = Path::new(&project.root);
+    let output = Path::new(&project_arg.save_path).to_path_buf();
+    match &mut project_arg.command {
+        Some(args::Command::Check(check)) => {
+            check.baseline = project_path(project, root, &check.baseline)?;
+            if let Some(file) = &mut check.output {
+                *file = project_path(project, &output, file)?;
+            }
+        }
+        Some(args::Command::Codegen(codegen)) => {
+            if let Some(file) = &mut codegen.output {
+                *file = project_path(project, root, file)?;
+            }
+        }
+        Some(args::Command::Coverage(coverage)) => {
+            if let Some(file) = &mut coverage.output {
+                *file = project_path(project, &output, file)?;
+            }
+        }
+        Some(args::Command::Alerts(alerts)) => {
+            if let Some(file) = &mut alerts.output {
+                *file = project_path(project, &output, file)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(project_arg)
+}
+
+/// `file` below `dir`; an absolute path would be shared by every project.
+fn project_path(project: &Project, dir: &Path, file: &str) -> Result<String, Box<dyn Error>> {
+    if Path::new(file).is_absolute() {
+        return Err(format!(
+            "{}: {file} would be written by every project, use a relative path",
+            project.name
+        )
+        .into());
+    }
+    Ok(dir.join(file).display().to_string())
+}
+
+/// Source files of a project, without those below its excluded directories.
+fn project_files(project: &Project, language: &Language) -> Result<Vec<String>, Box<dyn Error>> {
+    let extensions = crate::language::Language::from(language).extensions();
+    let mut files = files::list_files_in_dir(&vec![&project.root], true, extensions)?;
+    files.retain(|file| {
+        !project
+            .exclude
+            .iter()
+            .any(|dir| Path::new(file).starts_with(dir))
+    });
+    Ok(files)
+}
+
+fn project_language(project: &Project) -> Result<Language, Box<dyn Error>> {
+    Language::from_str(&project.language, true).map_err(|e| {
+        format!(
+            "{}: unknown language {}: {e}",
+            project.name, project.language
+        )
+        .into()
+    })
+}
+
+struct Summary {
+    name: String,
+    language: String,
+    output: PathBuf,
+    /// Documents per level, `None` when split into group directories
+    documents: Option<Vec<PathBuf>>,
+    counts: Vec<usize>,
+}
+
+impl Summary {
+    fn new(project: &Project, arg: &Arg, metas: &[Meta]) -> Summary {
+        let levels = Level::all();
+        let documents = match arg.group_output {
+            GroupOutput::Files if arg.group_by != args::GroupBy::None => None,
+            _ => Some(
+                levels
+                    .iter()
+                    .map(|level| PathBuf::from(files::form_file_name(&arg.save_path, arg, level)))
+                    .collect(),
+            ),
+        };
+        Summary {
+            name: project.name.clone(),
+            language: project.language.clone(),
+            output: PathBuf::from(&arg.save_path),
+            documents,
+            counts: levels
+                .iter()
+                .map(|level| metas.iter().filter(|m| m.level == *level).count())
+                .collect(),
+        }
+    }
+}
+
+fn render_index(index: &Path, summaries: &[Summary]) -> String {
+    let base = index.parent().unwrap_or(Path::new(""));
+    let levels = Level::all();
+    let mut data = String::from("# Log catalogs\n\n|project|language|");
+    for level in &levels {
+        data.push_str(&format!("{level}|"));
+    }
+    data.push_str("\n|---|---|");
+    data.push_str(&"---|".repeat(levels.len()));
+    data.push('\n');
+    for summary in summaries {
+        data.push_str(&format!(
+            "|[{}]({})|{}|",
+            summary.name,
+            link(base, &summary.output),
+            summary.language
+        ));
+        for (i, count) in summary.counts.iter().enumerate() {
+            match &summary.documents {
+                Some(documents) => {
+                    data.push_str(&format!("[{count}]({})|", link(base, &documents[i])))
+                }
+                None => data.push_str(&format!("{count}|")),
+            }
+        }
+        data.push('\n');
+    }
+    data
+}
+
+/// Link from the index directory `base` to `target`, going up with `..`
+/// where `target` doesn't lie below it.
+fn link(base: &Path, target: &Path) -> String {
+    let (base, target) = if base.is_absolute() == target.is_absolute() {
+        (base.to_path_buf(), target.to_path_buf())
+    } else {
+        (
+            std::path::absolute(base).unwrap_or_else(|_| base.to_path_buf()),
+            std::path::absolute(target).unwrap_or_else(|_| target.to_path_buf()),
+        )
+    };
+    let base = normalize(&base);
+    let target = normalize(&target);
+    let common = base
+        .iter()
+        .zip(target.iter())
+        .take_while(|(b, t)| b == t)
+        .count();
+    let mut parts = vec!["..".to_owned(); base.len() - common];
+    parts.extend(target[common..].iter().cloned());
+    if parts.is_empty() {
+        ".".to_owned()
+    } else {
+        parts.join("/")
+    }
+}
+
+/// Components of a path with `.` dropped and `dir/..` folded.
+fn normalize(path: &Path) -> Vec<String> {
+    let mut parts: Vec<String> = vec![];
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir if parts.last().is_some_and(|p| p != "..") => {
+                parts.pop();
+            }
+            c => parts.push(c.as_os_str().to_string_lossy().to_string()),
+        }
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use clap::Parser;
+
+    use super::{link, project_arg, render_index, Project, Summary, Workspace};
+    use crate::args::{Arg, Command};
+
+    #[test]
+    fn parse_workspace() {
+        let workspace: Workspace = toml::from_str(
+            r#"
+index = "docs/index.md"
+
+[[project]]
+name = "billing"
+root = "services/billing"
+language = "golang"
+output = "docs/billing"
+
+[[project]]
+name = "web"
+root = "web"
+language = "typescript"
+"#,
+        )
+        .unwrap();
+        assert_eq!(workspace.index.as_deref(), Some("docs/index.md"));
+        assert_eq!(workspace.projects.len(), 2);
+        assert_eq!(workspace.projects[1].output, None);
+    }
+
+    #[test]
+    fn discover_projects() {
+        let root = std::env::temp_dir().join(format!("logdoc-discover-{}", std::process::id()));
+        for (dir, file) in [
+            ("", "Cargo.toml"),
+            ("billing", "go.mod"),
+            ("billing", "main.go"),
+            ("billing/tools", "Cargo.toml"),
+            ("web", "package.json"),
+            ("web/api", "package.json"),
+            ("services/api", "go.mod"),
+            ("node_modules/dep", "package.json"),
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(root.join(dir).join(file), "").unwrap();
+        }
+        let projects = Workspace::discover(&root).unwrap().projects;
+        std::fs::remove_dir_all(&root).unwrap();
+        let names: Vec<(&str, &str)> = projects
+            .iter()
+            .map(|p| (p.name.as_str(), p.language.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("billing", "golang"),
+                ("tools", "rust"),
+                ("services-api", "golang"),
+                ("web-api", "javascript"),
+            ]
+        );
+        assert_eq!(
+            projects[0].exclude,
+            [root.join("billing/tools").display().to_string()]
+        );
+    }
+
+    #[test]
+    fn project_paths() {
+        let project = Project {
+            name: "billing".to_owned(),
+            root: "services/billing".to_owned(),
+            language: "golang".to_owned(),
+            output: None,
+            exclude: vec![],
+        };
+        let arg = Arg::try_parse_from([
+            "logdoc",
+            "--discover",
+            "services",
+            "-s",
+            "docs",
+            "check",
+            "-o",
+            "findings.txt",
+        ])
+        .unwrap();
+        let Some(Command::Check(check)) = project_arg(&arg, &project).unwrap().command else {
+            panic!("expected check");
+        };
+        assert_eq!(check.baseline, "services/billing/logdoc-baseline.json");
+        assert_eq!(check.output.as_deref(), Some("docs/billing/findings.txt"));
+
+        let arg = Arg::try_parse_from([
+            "logdoc",
+            "--discover",
+            "services",
+            "coverage",
+            "-o",
+            "/tmp/coverage.md",
+        ])
+        .unwrap();
+        assert!(project_arg(&arg, &project).is_err());
+    }
+
+    #[test]
+    fn index() {
+        assert_eq!(
+            link(Path::new("docs"), Path::new("docs/billing/info.md")),
+            "billing/info.md"
+        );
+        assert_eq!(link(Path::new("."), Path::new("./web")), "web");
+        assert_eq!(
+            link(Path::new("docs/index"), Path::new("out/billing")),
+            "../../out/billing"
+        );
+        assert_eq!(link(Path::new("docs"), Path::new("docs/../out")), "../out");
+        let project = Project {
+            name: "billing".to_owned(),
+            root: "billing".to_owned(),
+            language: "golang".to_owned(),
+            output: None,
+            exclude: vec![],
+        };
+        let summary = Summary {
+            name: project.name,
+            language: project.language,
+            output: PathBuf::from("docs/billing"),
+            documents: None,
+            counts: vec![2, 0, 0, 1, 0],
+        };
+        assert_eq!(
+            render_index(Path::new("docs/index.md"), &[summary]),
+            "# Log catalogs\n\n|project|language|info|debug|trace|warn|fatal|\n\
+             |---|---|---|---|---|---|---|\n|[billing](billing)|golang|2|0|0|1|0|\n"
+        );
+    }
+}
@@ -0,0 +1,23 @@
+#include <stdio.h>
+#include <syslog.h>
+
+int open_config(const char *path)
+{
+    /*
+     * warn: config %s not found
+     * defaults are used
+     * create the config file to silence this warning
+     */
+    syslog(LOG_WARNING, "config %s not found", path);
+
+    /** fatal: out of memory */
+    return -1;
+}
+
+int main(void)
+{
+    /// info: daemon started
+    /// daemon is running
+    printf("daemon started\n");
+    return 0;
+}
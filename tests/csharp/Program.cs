@@ -0,0 +1,32 @@
+using Microsoft.Extensions.Logging;
+
+namespace Shop.Orders;
+
+public class OrderService
+{
+    private readonly ILogger<OrderService> _logger;
+
+    public OrderService(ILogger<OrderService> logger)
+    {
+        _logger = logger;
+    }
+
+    public Order? Find(string id)
+    {
+        /// warn: order {OrderId} not found
+        /// <summary>lookup returned no order</summary>
+        /// <remarks>
+        /// the client may use a stale link
+        /// </remarks>
+        _logger.LogWarning("Order {OrderId} not found", id);
+        return null;
+    }
+
+    public void Pay(Order order)
+    {
+        // fatal: payment gateway is unreachable
+        // orders can't be paid
+        // check gateway credentials
+        Log.Fatal(@"Payment gateway is unreachable");
+    }
+}
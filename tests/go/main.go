@@ -24,3 +24,7 @@ func main() {
 	// only subject
 	log.Info("other info log")
 }
+
+func open(p string) error {
+	return fmt.Errorf("empty path %s", p)
+}
@@ -0,0 +1,30 @@
+package billing
+
+func charge(ctx context.Context, order Order) error {
+	if err := gateway.Charge(order); err != nil {
+		// warn: charge failed
+		// payment gateway declined the order
+		slog.Warn("charge failed", "order", order.ID, slog.Int("attempt", order.Attempt))
+		return err
+	}
+	// info: request served
+	// response is sent
+	logger.Info("request served", zap.String("url", url), zap.Error(err))
+	return nil
+}
+
+func login(user string, err error) {
+	// debug: login failed
+	// wrong password
+	log.Debug().Str("user", user).Int("attempt", 3).Err(err).Msg("login failed")
+
+	// fatal: cannot open %s
+	// the store is unavailable
+	logrus.WithFields(logrus.Fields{"order": id}).WithField("user", user).Fatalf("cannot open %s", path)
+}
+
+func serve(peer string) {
+	// warn: slow request
+	// the peer reads slowly
+	logger.Named("http").With(zap.String("peer", peer)).Warn("slow request")
+}
@@ -0,0 +1,24 @@
+package shop.orders
+
+import io.github.oshai.kotlinlogging.KotlinLogging
+
+private val logger = KotlinLogging.logger {}
+
+class OrderService(private val orders: OrderRepository) {
+    fun find(id: String): Order? {
+        // warn: order %s not found
+        // lookup returned no order
+        // the client may use a stale link
+        logger.warn { "Order $id not found" }
+        return orders.byId(id)
+    }
+
+    /**
+     * fatal: payment gateway is unreachable
+     * orders can't be paid
+     * check gateway credentials
+     */
+    fun pay(order: Order) {
+        logger.error("Payment gateway is unreachable")
+    }
+}
@@ -0,0 +1,20 @@
+local log = require("log")
+
+local M = {}
+
+function M.fetch(queue)
+  -- warn: queue %s is empty
+  -- nothing to process
+  -- producers may be stopped
+  log.warn(string.format("queue %s is empty", queue))
+  return nil
+end
+
+function M.store(job)
+  --[[ fatal: job store is unavailable
+  jobs are lost
+  check the redis connection ]]
+  ngx.log(ngx.ERR, "job store is unavailable")
+end
+
+return M
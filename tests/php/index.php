@@ -0,0 +1,25 @@
+<?php
+
+namespace App\Billing;
+
+class InvoiceService
+{
+    public function send(Invoice $invoice): void
+    {
+        // warn: invoice %s has no recipient
+        // invoice skipped
+        // the customer has no e-mail address
+        $this->logger->warning("Invoice {$invoice->id} has no recipient");
+    }
+
+    public function charge(Invoice $invoice): void
+    {
+        # fatal: payment provider rejected the api key
+        # billing is stopped
+        Log::error('Payment provider rejected the API key');
+    }
+}
+
+// info: billing worker started
+// worker is running
+error_log("billing worker started");
@@ -9,3 +9,46 @@ def main:
     # FATAL: fatal message from python
     # emergency, do something
     fatal("fatal message from python")
+
+
+logger = logging.getLogger(__name__)
+
+
+def charge(order):
+    """Charge the order.
+
+    warn: payment for order %s declined
+    the card was declined by the bank
+    ask the customer for another card
+
+    Args:
+        order: order to charge
+    """
+    logger.warning("payment for order %s declined", order.id, extra={"order": order.id})
+
+
+def connect():
+    try:
+        db.connect()
+    except ConnectionError:
+        logging.exception("database is unreachable")  # fatal: database is unreachable
+        # the service can't store data
+        raise
+
+
+def reload():
+    log = structlog.get_logger()
+    # debug: config reloaded
+    # new config is applied
+    log.debug("config reloaded", path=CONFIG)
+
+
+def migrate(db):
+    """Apply pending schema migrations.
+
+    Warn: locks the tables
+    run it in a maintenance window
+    """
+    # WARNING: not thread-safe
+    # hold the migration lock
+    db.execute(MIGRATIONS)
@@ -0,0 +1,37 @@
+use log::warn;
+use tracing::instrument;
+
+pub struct Service {
+    db: Db,
+    log: slog::Logger,
+}
+
+impl Service {
+    pub fn connect(&self, user_id: u64) -> Result<(), Error> {
+        if let Err(err) = self.db.connect() {
+            // warn: connection lost: {}
+            // database connection dropped
+            // the request is retried
+            warn!(target: "db", user_id, "connection lost: {}", err);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Charge the order of a user.
+    #[instrument(skip(self), name = "charge")]
+    pub fn charge(&self, id: u64) {
+        if let Err(err) = self.db.charge(id) {
+            /* fatal: payment failed
+             * the order is not charged
+             */
+            tracing::error!(user.id = %id, ?err, "payment failed");
+        }
+    }
+
+    pub fn start(&self, port: u16) {
+        //! info: service started
+        //! service accepts requests
+        slog::info!(self.log, "service started"; "port" => port, "tls" => true);
+    }
+}
@@ -0,0 +1,14 @@
+#!/bin/bash
+set -eu
+
+# info: backup started
+# nightly backup is running
+logger -t backup -p user.info "backup started"
+
+if ! tar czf "$TARGET" /var/lib/app; then
+    # fatal: backup archive can't be written
+    # no backup for today
+    # check free space on the backup volume
+    echo "ERROR: backup archive can't be written" >&2
+    exit 1
+fi
@@ -0,0 +1,20 @@
+import React from "react";
+
+export const App = ({ user }: Props) => {
+  const onClick = () => {
+    // debug: button clicked
+    // user pressed the main button
+    console.debug("button clicked");
+  };
+  return <button onClick={onClick}>{user.name}</button>;
+};
+
+@observer
+class Profile extends React.Component<Props> {
+  render() {
+    // trace: profile rendered
+    // render of the profile card
+    console.trace("profile rendered");
+    return <div>{this.props.user.name}</div>;
+  }
+}
@@ -0,0 +1,26 @@
+import { Injectable } from "@nestjs/common";
+
+// info: service started
+// service is ready to accept requests
+console.info("service started");
+
+@Injectable()
+export class UserService {
+  constructor(private readonly logger: Logger) {}
+
+  @Retry(3)
+  async find(id: string): Promise<User | undefined> {
+    // warn: user %s not found
+    // lookup by id returned nothing
+    // check that the id comes from a valid session
+    this.logger.warn(`user ${id} not found`);
+    return undefined;
+  }
+}
+
+export const handler = async (event: Event): Promise<void> => {
+  // FATAL: cannot parse event
+  // event payload is not valid JSON
+  // inspect the producer
+  logger.error("cannot parse event");
+};